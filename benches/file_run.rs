@@ -0,0 +1,72 @@
+//! File run over a large file cycled many times.
+//!
+//! Each iteration imports the file and reads every command through `Console::read` until the
+//! run ends. The file cursor holds indexes and resolves each command by reference when it is
+//! read, so the time per command stays flat as the cycle times grow.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rfcaf::interface::{ConsoleLog, LogLevel};
+use rfcaf::{Console, FileFormat};
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+
+const INSTRUCTIONS: usize = 20;
+const COMMANDS: usize = 5;
+
+/// Console log dropping everything, so that only the console itself is measured.
+struct NullLog;
+
+impl ConsoleLog for NullLog {
+    fn log(&self, _level: LogLevel, _log_info: &str) {}
+}
+
+/// TOML file of the instruction sets with their commands, cycled the times.
+fn content(cycle_times: usize) -> String {
+    let mut content = format!("cycle_times = {}\n", cycle_times);
+    for ins in 0..INSTRUCTIONS {
+        content.push_str(&format!(
+            "\n[[exc_ins_assets]]\nexc_ins = \"ins {}\"\n",
+            ins
+        ));
+        for cmd in 0..COMMANDS {
+            content.push_str(&format!(
+                "[[exc_ins_assets.sub_cmd_assets]]\nsub_cmd = \"ins {} cmd {} with a payload\"\n",
+                ins, cmd
+            ));
+        }
+    }
+    content
+}
+
+fn file_run(c: &mut Criterion) {
+    let mut group = c.benchmark_group("file_run");
+    for cycle_times in [10, 100, 1000] {
+        let content = content(cycle_times);
+        let commands = (INSTRUCTIONS * (1 + COMMANDS) * cycle_times) as u64;
+        group.throughput(Throughput::Elements(commands));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(cycle_times),
+            &content,
+            |b, content| {
+                b.iter(|| {
+                    let mut console = Console::new(Arc::new(Mutex::new(NullLog)));
+                    console.set_quiet(true);
+                    console.set_log_timestamps(false);
+                    console.set_input(Cursor::new(Vec::new()));
+                    console.setup();
+                    console.import_from_str(content, FileFormat::Toml).unwrap();
+                    console.run_to_completion().unwrap();
+                    black_box(console.run_summary().commands)
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = file_run
+}
+criterion_main!(benches);
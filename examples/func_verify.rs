@@ -1,7 +1,8 @@
 use std::sync::{Arc, Mutex};
 
 use rfcaf::interface::ConsoleLog;
-const ERR_VALID_INPUT: &'static str = "无效的输入";
+use rfcaf::DataError;
+const ERR_VALID_INPUT: &str = "无效的输入";
 extern crate rfcaf;
 struct Log {
     err_info: &'static str,
@@ -27,13 +28,16 @@ fn main() {
     test.setup();
 
     loop {
-        if let Ok(cmd) = test.read("输入一条命令") {
-            match cmd.as_str() {
+        match test.read("输入一条命令") {
+            Ok(cmd) => match cmd.as_str() {
                 "R" | "r" => {
                     test.file_import_no_err();
                 }
                 _ => {}
-            };
+            },
+            // the terminal input is closed.
+            Err(DataError::Eof) => break,
+            Err(_) => {}
         }
     }
 }
@@ -0,0 +1,37 @@
+use crate::color;
+use crate::interface::{ConsoleLog, LogLevel};
+use std::cell::RefCell;
+
+/// Console log keeping every logged message in memory instead of printing it,
+/// to inspect what the console produced, e.g. in tests. The colors are stripped.
+#[derive(Debug, Default)]
+pub struct CaptureLog {
+    lines: RefCell<Vec<String>>,
+}
+
+impl CaptureLog {
+    pub fn new() -> Self {
+        CaptureLog::default()
+    }
+
+    /// Snapshot of the logged messages in order, including the terminal command execution
+    /// output. The messages are appended through `&self`, so it is a copy, not a borrowed slice.
+    pub fn lines(&self) -> Vec<String> {
+        self.lines.borrow().clone()
+    }
+
+    /// Drop the logged messages.
+    pub fn clear(&self) {
+        self.lines.borrow_mut().clear();
+    }
+}
+
+impl ConsoleLog for CaptureLog {
+    fn log(&self, _level: LogLevel, log_info: &str) {
+        self.lines.borrow_mut().push(color::strip(log_info));
+    }
+
+    fn terminal_exc_log(&self, log_info: &str) {
+        self.log(LogLevel::Info, log_info);
+    }
+}
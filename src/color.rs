@@ -0,0 +1,55 @@
+use std::fmt;
+
+pub(crate) const RED: &str = "31"; // error output.
+pub(crate) const CYAN: &str = "36"; // prompt output.
+pub(crate) const YELLOW: &str = "33"; // state transition output.
+
+/// Log value wrapped in the ANSI color code when enabled.
+pub(crate) struct Paint<V> {
+    pub(crate) code: &'static str,
+    pub(crate) value: V,
+    pub(crate) enabled: bool,
+}
+
+impl<V: fmt::Display> fmt::Display for Paint<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.enabled {
+            true => write!(f, "\x1b[{}m{}\x1b[0m", self.code, self.value),
+            false => write!(f, "{}", self.value),
+        }
+    }
+}
+
+impl<V: fmt::Debug> fmt::Debug for Paint<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.enabled {
+            true => write!(f, "\x1b[{}m{:?}\x1b[0m", self.code, self.value),
+            false => write!(f, "{:?}", self.value),
+        }
+    }
+}
+
+/// The text without its ANSI color codes, for the outputs rendering no colors.
+pub(crate) fn strip(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => {
+                // skip the escape sequence up to its final `m`.
+                for c in chars.by_ref() {
+                    if c == 'm' {
+                        break;
+                    }
+                }
+            }
+            _ => plain.push(c),
+        }
+    }
+    plain
+}
+
+/// Whether colors are enabled by default, the `NO_COLOR` environment variable disables them.
+pub(crate) fn color_default() -> bool {
+    cfg!(feature = "colored") && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+}
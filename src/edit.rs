@@ -0,0 +1,186 @@
+use crate::interface::ConsoleLog;
+use crate::{label_check, Console, DataError, ExecuteAssets, ExecuteFile};
+
+impl<T> Console<T>
+where
+    T: ConsoleLog,
+{
+    /// Append the instruction set to the loaded command set, it executes after the pending
+    /// ones in each remaining cycle. Without a file run, it starts a new file run.
+    pub fn append_instruction(&mut self, ins: ExecuteAssets) -> Result<(), DataError> {
+        if self.auto_exc.next_exc_ins.is_none() {
+            return self.load(ExecuteFile::new(vec![ins]));
+        }
+        self.edit_check()?;
+
+        let ins_index = self.auto_exc.exc_ins_assets.len();
+        self.auto_exc.exc_ins_assets.push(ins);
+        if let Err(err_info) = self.labels_rebuild() {
+            self.auto_exc.exc_ins_assets.pop();
+            return Err(err_info);
+        }
+        if !self.auto_exc.order.is_empty() {
+            self.auto_exc.order.push(ins_index);
+        }
+        self.progress_reset();
+        Ok(())
+    }
+
+    /// Insert the instruction set at the index of the loaded command set, the later ones shift
+    /// back and the file run in progress continues from the same instruction set. Without a
+    /// file run, it starts a new file run.
+    pub fn insert_instruction(
+        &mut self,
+        index: usize,
+        ins: ExecuteAssets,
+    ) -> Result<(), DataError> {
+        self.index_check(index, self.auto_exc.exc_ins_assets.len() + 1)?;
+        if self.auto_exc.next_exc_ins.is_none() {
+            return self.load(ExecuteFile::new(vec![ins]));
+        }
+        self.edit_check()?;
+
+        self.auto_exc.exc_ins_assets.insert(index, ins);
+        if let Err(err_info) = self.labels_rebuild() {
+            self.auto_exc.exc_ins_assets.remove(index);
+            return Err(err_info);
+        }
+        self.ins_shift(|v| if v >= index { v + 1 } else { v });
+        if !self.auto_exc.order.is_empty() {
+            let ins_pos = index.min(self.auto_exc.order.len());
+            self.auto_exc.order.insert(ins_pos, index);
+        }
+        self.progress_reset();
+        Ok(())
+    }
+
+    /// Remove the instruction set of the index from the loaded command set, the later ones
+    /// shift forward and the file run in progress continues from the same instruction set.
+    ///
+    /// The executing instruction set can only be removed while the console is paused, the file
+    /// run then continues from the next one. The last instruction set can not be removed,
+    /// abort the file run instead.
+    pub fn remove_instruction(&mut self, index: usize) -> Result<ExecuteAssets, DataError> {
+        self.edit_check()?;
+        self.index_check(index, self.auto_exc.exc_ins_assets.len())?;
+        if self.auto_exc.exc_ins_assets.len() == 1 {
+            return Err(DataError::Redaction(
+                "不能移除最后一条主指令集，请中止文件执行。".to_string(),
+            ));
+        }
+        let current = self.auto_exc.next_exc_ins == Some(index);
+        if current && !self.paused {
+            return Err(DataError::Redaction(format!(
+                "第 {} 条主指令集正在执行，暂停后才能移除。",
+                index + 1
+            )));
+        }
+
+        let ins_pos = self.ins_pos(index);
+        let ins = self.auto_exc.exc_ins_assets.remove(index);
+        if let Err(err_info) = self.labels_rebuild() {
+            self.auto_exc.exc_ins_assets.insert(index, ins);
+            return Err(err_info);
+        }
+        if !self.auto_exc.order.is_empty() {
+            self.auto_exc.order.remove(ins_pos);
+        }
+        self.ins_shift(|v| if v > index { v - 1 } else { v });
+
+        if current {
+            // the commands of the removed instruction set are no longer reported.
+            self.served = None;
+            self.retry = None;
+            self.deadline = None;
+            self.ins_advance(ins_pos)?;
+            self.refresh()?;
+        }
+        self.progress_reset();
+        Ok(ins)
+    }
+
+    /// Move the instruction set from the index to the other index of the loaded command set,
+    /// the ones between shift. The file run must be paused, it continues from the same
+    /// instruction set at its new position: the instruction sets now before it are not executed
+    /// again in the current cycle, the ones now after it are.
+    pub fn move_instruction(&mut self, from: usize, to: usize) -> Result<(), DataError> {
+        self.edit_check()?;
+        let len = self.auto_exc.exc_ins_assets.len();
+        self.index_check(from, len)?;
+        self.index_check(to, len)?;
+        if self.auto_exc.next_exc_ins.is_some() && !self.paused {
+            return Err(DataError::Redaction(
+                "文件执行中不能调整主指令集顺序，请先暂停。".to_string(),
+            ));
+        }
+
+        let ins = self.auto_exc.exc_ins_assets.remove(from);
+        self.auto_exc.exc_ins_assets.insert(to, ins);
+        self.labels_rebuild()?;
+        self.ins_shift(|v| match v {
+            _ if v == from => to,
+            _ if from < v && v <= to => v - 1,
+            _ if to <= v && v < from => v + 1,
+            _ => v,
+        });
+        self.progress_reset();
+        Ok(())
+    }
+
+    /// Reject the index out of the range `0..len`.
+    fn index_check(&self, index: usize, len: usize) -> Result<(), DataError> {
+        match index < len {
+            true => Ok(()),
+            false => Err(DataError::Redaction(format!(
+                "第 {} 条主指令集不存在，主指令集共 {} 条。",
+                index + 1,
+                len
+            ))),
+        }
+    }
+
+    /// Move the instruction set indexes held by the file run after an edit.
+    fn ins_shift(&mut self, shift: impl Fn(usize) -> usize) {
+        self.auto_exc.next_exc_ins = self.auto_exc.next_exc_ins.map(&shift);
+        for ins_index in self.auto_exc.order.iter_mut() {
+            *ins_index = shift(*ins_index);
+        }
+        let retry = self.retry.as_mut();
+        for served in self
+            .served
+            .iter_mut()
+            .chain(retry.and_then(|v| v.served.as_mut()))
+        {
+            served.ins_index = shift(served.ins_index);
+        }
+        let retry = self.retry.as_mut();
+        for deadline in self
+            .deadline
+            .iter_mut()
+            .chain(retry.and_then(|v| v.deadline.as_mut()))
+        {
+            deadline.ins_index = shift(deadline.ins_index);
+        }
+    }
+
+    /// Reject the edit of the streamed command list, only its current instruction set is kept.
+    fn edit_check(&self) -> Result<(), DataError> {
+        match self.auto_exc.stream {
+            Some(_) => Err(DataError::Redaction(
+                "流式读取的命令列表不支持编辑。".to_string(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Re-index the labels of the edited command set, the edit is rejected on the duplicate
+    /// labels and the jumps to undefined labels.
+    fn labels_rebuild(&mut self) -> Result<(), DataError> {
+        let (labels, errors) = label_check(&self.auto_exc.exc_ins_assets, &self.messages);
+        if let Some(err_info) = errors.into_iter().next() {
+            return Err(err_info);
+        }
+        self.auto_exc.labels = labels;
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+use crate::color;
+use crate::interface::{ConsoleLog, LogLevel};
+use std::cell::RefCell;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Console log tee'd to stdout and a file opened in append mode, used like any other log
+/// through `Arc<Mutex<FileLog>>`. The colors are only rendered on stdout.
+///
+/// The IO errors of the file are reported on stderr, the console keeps running.
+#[derive(Debug)]
+pub struct FileLog {
+    path: PathBuf,
+    file: RefCell<Option<File>>, // <None after a failed rotation, reopened on the next write> opened log file.
+    max_size: Option<u64>,       // <option> size in bytes from which the file is rotated.
+    backups: usize,              // number of rotated files kept as `<path>.1`, `<path>.2`, ...
+}
+
+impl FileLog {
+    pub fn new(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(FileLog {
+            path,
+            file: RefCell::new(Some(file)),
+            max_size: None,
+            backups: 1,
+        })
+    }
+
+    /// Rotate the file once it reaches `max_size` bytes, keeping `backups` rotated files.
+    pub fn rotation(mut self, max_size: u64, backups: usize) -> Self {
+        self.max_size = Some(max_size);
+        self.backups = backups.max(1);
+        self
+    }
+
+    /// Append the line to the file, rotating it first when it is full.
+    fn write_line(&self, line: &str) -> io::Result<()> {
+        let mut file = self.file.borrow_mut();
+        if let (Some(max_size), Some(v)) = (self.max_size, file.as_ref()) {
+            let size = v.metadata()?.len();
+            if size > 0 && size + line.len() as u64 + 1 > max_size {
+                *file = None;
+                self.rotate()?;
+            }
+        }
+        // reopen the file after rotation, or after a previous failure.
+        if file.is_none() {
+            *file = Some(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path)?,
+            );
+        }
+        if let Some(v) = file.as_mut() {
+            writeln!(v, "{}", line)?;
+        }
+        Ok(())
+    }
+
+    /// Shift the rotated files by one, the current file becomes `<path>.1`.
+    fn rotate(&self) -> io::Result<()> {
+        for index in (1..self.backups).rev() {
+            let from = self.backup_path(index);
+            if from.exists() {
+                fs::rename(&from, self.backup_path(index + 1))?;
+            }
+        }
+        if self.path.exists() {
+            fs::rename(&self.path, self.backup_path(1))?;
+        }
+        Ok(())
+    }
+
+    fn backup_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        PathBuf::from(path)
+    }
+}
+
+impl ConsoleLog for FileLog {
+    fn log(&self, _level: LogLevel, log_info: &str) {
+        println!("{}", log_info);
+        if let Err(err_info) = self.write_line(&color::strip(log_info)) {
+            eprintln!("日志文件 {} 写入失败：{}", self.path.display(), err_info);
+        }
+    }
+}
@@ -1,24 +1,223 @@
+use crate::RunSummary;
 use std::fmt;
+use std::io::{self, BufRead, BufReader, Cursor, IsTerminal, Read, Stdin};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Severity of the console log information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/// Banner of the console state transition notice.
+pub(crate) fn state_banner(prev: &str, next: &str) -> String {
+    format!(
+        "
++ - - - - - - - - - + - - - - - - - - - - - - - - - - - - - - +
+|   控制台当前状态  |  {} -> {}
++ - - - - - - - - - + - - - - - - - - - - - - - - - - - - - - +",
+        prev, next
+    )
+}
+
+/// Error information of `err_dyn_log`, anything displayable.
+pub trait ErrInfo: fmt::Display + fmt::Debug {}
+
+impl<T: fmt::Display + fmt::Debug + ?Sized> ErrInfo for T {}
 
 pub trait ConsoleLog {
-    fn prompt_log(&self, log_info: &str) {
+    /// Every log information is routed here by default, override it to filter or route by level.
+    fn log(&self, _level: LogLevel, log_info: &str) {
         println!("{}", log_info);
     }
 
+    fn prompt_log(&self, log_info: &str) {
+        self.log(LogLevel::Info, log_info);
+    }
+
     fn file_exc_log(&self, log_info: &str) {
-        println!("{}", log_info);
+        self.log(LogLevel::Info, log_info);
     }
 
     fn terminal_exc_log(&self, _log_info: &str) {}
 
+    /// Internal tracing of the file cursor and the state refreshes, with `set_verbose`.
+    fn debug_log(&self, log_info: &str) {
+        self.log(LogLevel::Debug, log_info);
+    }
+
     fn err_log<T>(&self, err_info: T)
     where
         T: fmt::Display + fmt::Debug,
+        Self: Sized,
     {
-        println!("{:?}", err_info);
+        self.err_dyn_log(&err_info);
+    }
+
+    /// Object-safe form of `err_log` which it calls by default, override this one to format
+    /// the errors the same way through `dyn ConsoleLog`, e.g. in `MultiLog`.
+    fn err_dyn_log(&self, err_info: &dyn ErrInfo) {
+        self.log(LogLevel::Error, &format!("{:?}", err_info));
+    }
+
+    /// Console state transition notice.
+    fn state_log(&self, prev: &str, next: &str) {
+        self.log(LogLevel::Debug, &state_banner(prev, next));
+    }
+
+    fn run_summary_log(&self, summary: &RunSummary) {
+        let mut log_info = format!(
+            " * 执行命令 {} 条，失败 {} 条，耗时 {} ms",
+            summary.commands,
+            summary.failures,
+            summary.duration.as_millis()
+        );
+        if summary.retries != 0 {
+            log_info.push_str(&format!("，重试 {} 次", summary.retries));
+        }
+        self.log(LogLevel::Info, &log_info);
+    }
+
+    /// Breakpoint of the instruction set of the index pausing the file run.
+    fn breakpoint_log(&self, ins_index: usize) {
+        self.log(
+            LogLevel::Info,
+            &format!(" * 断点：第 {} 条主指令集前暂停", ins_index + 1),
+        );
+    }
+
+    /// Description of the instruction set of the index as it begins executing.
+    fn desc_log(&self, ins_index: usize, desc: &str) {
+        self.log(
+            LogLevel::Info,
+            &format!(" * 步骤 {}：{}", ins_index + 1, desc),
+        );
+    }
+
+    /// Time the command took from the start of its read to the start of the next read,
+    /// nothing by default.
+    fn timing_log(&self, _cmd: &str, _elapsed: Duration) {}
+
+    /// Progress after each command read from the file, e.g. `[ 34 / 120 ]`, nothing by default.
+    fn progress_log(&self, _read: usize, _total: usize) {}
+
+    /// Expectation results of a finished file run, override it with a no-op to suppress it.
+    fn expect_summary_log(&self, summary: &RunSummary) {
+        let mut log_info = format!(
+            " * 期望输出通过 {} 条，失败 {} 条",
+            summary.passed, summary.failed
+        );
+        for (ins_index, cmd_index) in &summary.failing {
+            log_info.push_str(&format!(
+                "\n   - 第 {} 条主指令集的第 {} 条子命令",
+                ins_index + 1,
+                cmd_index + 1
+            ));
+        }
+        self.log(LogLevel::Info, &log_info);
+    }
+
+    /// Timestamp prepended to the prompt and command log information, RFC3339 in UTC by default.
+    fn timestamp(&self) -> String {
+        let elapsed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let secs = elapsed.as_secs();
+        let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+            year,
+            month,
+            day,
+            secs % 86_400 / 3_600,
+            secs % 3_600 / 60,
+            secs % 60,
+            elapsed.subsec_millis()
+        )
     }
 
     fn err_invalid(&self) -> &'static str {
         "invalid input."
     }
 }
+
+/// Line source of the terminal commands, the stdin by default.
+pub trait ConsoleInput {
+    /// Read a line including its line ending into the buffer, 0 at the end of the input.
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize>;
+
+    /// Read a line like `read_line` without echoing it, for passwords and keys.
+    fn read_secret_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        self.read_line(buf)
+    }
+
+    /// Read a line like `read_line` without recalling it later, for the answers of the
+    /// interactive prompts, e.g. `confirm`.
+    fn read_answer_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        self.read_line(buf)
+    }
+
+    /// Forget the recalled lines of the input, nothing by default.
+    fn clear_history(&mut self) {}
+
+    /// Whether the lines read from now on are recalled, nothing by default.
+    fn set_history_enabled(&mut self, _enabled: bool) {}
+}
+
+/// Read a line from the terminal without echo, or from stdin when it is not a terminal.
+pub(crate) fn stdin_secret_line(buf: &mut String) -> io::Result<usize> {
+    if !io::stdin().is_terminal() {
+        return io::stdin().read_line(buf);
+    }
+    let line = rpassword::read_password()?;
+    buf.push_str(&line);
+    buf.push('\n');
+    Ok(line.len() + 1)
+}
+
+impl fmt::Debug for dyn ConsoleInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ConsoleInput")
+    }
+}
+
+impl ConsoleInput for Stdin {
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        Stdin::read_line(self, buf)
+    }
+
+    fn read_secret_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        stdin_secret_line(buf)
+    }
+}
+
+impl<R: Read> ConsoleInput for BufReader<R> {
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        BufRead::read_line(self, buf)
+    }
+}
+
+impl<T: AsRef<[u8]>> ConsoleInput for Cursor<T> {
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        BufRead::read_line(self, buf)
+    }
+}
+
+/// Convert the days since the unix epoch to the (year, month, day) of the civil calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
@@ -0,0 +1,132 @@
+use crate::color;
+use crate::interface::{ConsoleLog, ErrInfo, LogLevel};
+use crate::RunSummary;
+use serde_json::json;
+use std::time::Duration;
+
+/// Console log emitting one JSON object per event on stdout, e.g.
+/// `{"kind":"file_exc","cmd":"...","ts":"..."}`, without the colors.
+///
+/// It is used like any other log, disable the console timestamps since every record has its own:
+///
+/// ```no_run
+/// use rfcaf::json_log::JsonLog;
+/// use std::sync::{Arc, Mutex};
+///
+/// let mut console = rfcaf::Console::new(Arc::new(Mutex::new(JsonLog::new())));
+/// console.set_log_timestamps(false);
+/// console.setup();
+/// ```
+#[derive(Debug, Default)]
+pub struct JsonLog;
+
+impl JsonLog {
+    pub fn new() -> Self {
+        JsonLog
+    }
+
+    /// Emit the record of the event kind.
+    fn emit(&self, kind: &str, cmd: &str) {
+        println!(
+            "{}",
+            json!({ "kind": kind, "cmd": color::strip(cmd), "ts": self.timestamp() })
+        );
+    }
+}
+
+impl ConsoleLog for JsonLog {
+    fn log(&self, level: LogLevel, log_info: &str) {
+        let level = format!("{:?}", level).to_lowercase();
+        println!(
+            "{}",
+            json!({ "kind": "log", "level": level, "cmd": color::strip(log_info), "ts": self.timestamp() })
+        );
+    }
+
+    fn prompt_log(&self, log_info: &str) {
+        self.emit("prompt", log_info);
+    }
+
+    fn file_exc_log(&self, log_info: &str) {
+        self.emit("file_exc", log_info);
+    }
+
+    fn terminal_exc_log(&self, log_info: &str) {
+        self.emit("terminal_exc", log_info);
+    }
+
+    fn debug_log(&self, log_info: &str) {
+        self.emit("debug", log_info);
+    }
+
+    fn err_dyn_log(&self, err_info: &dyn ErrInfo) {
+        self.emit("error", &err_info.to_string());
+    }
+
+    fn state_log(&self, prev: &str, next: &str) {
+        println!(
+            "{}",
+            json!({ "kind": "state", "prev": color::strip(prev), "next": color::strip(next), "ts": self.timestamp() })
+        );
+    }
+
+    fn run_summary_log(&self, summary: &RunSummary) {
+        println!(
+            "{}",
+            json!({
+                "kind": "summary",
+                "commands": summary.commands,
+                "failures": summary.failures,
+                "retries": summary.retries,
+                "duration_ms": summary.duration.as_millis() as u64,
+                "ts": self.timestamp(),
+            })
+        );
+    }
+
+    fn breakpoint_log(&self, ins_index: usize) {
+        println!(
+            "{}",
+            json!({ "kind": "breakpoint", "ins": ins_index, "ts": self.timestamp() })
+        );
+    }
+
+    fn desc_log(&self, ins_index: usize, desc: &str) {
+        println!(
+            "{}",
+            json!({ "kind": "desc", "ins": ins_index, "desc": desc, "ts": self.timestamp() })
+        );
+    }
+
+    fn timing_log(&self, cmd: &str, elapsed: Duration) {
+        println!(
+            "{}",
+            json!({
+                "kind": "timing",
+                "cmd": cmd,
+                "elapsed_ms": elapsed.as_millis() as u64,
+                "ts": self.timestamp(),
+            })
+        );
+    }
+
+    fn progress_log(&self, read: usize, total: usize) {
+        println!(
+            "{}",
+            json!({ "kind": "progress", "read": read, "total": total, "ts": self.timestamp() })
+        );
+    }
+
+    fn expect_summary_log(&self, summary: &RunSummary) {
+        println!(
+            "{}",
+            json!({
+                "kind": "expect_summary",
+                "passed": summary.passed,
+                "failed": summary.failed,
+                "failing": summary.failing,
+                "ts": self.timestamp(),
+            })
+        );
+    }
+}
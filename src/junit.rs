@@ -0,0 +1,99 @@
+use crate::interface::ConsoleLog;
+use crate::{Console, DataError, ExpectResult};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+impl<T> Console<T>
+where
+    T: ConsoleLog,
+{
+    /// Write the expectation results of the file run as a JUnit XML report, each instruction
+    /// set is a `<testsuite>` and each of its commands with an expected output a `<testcase>`.
+    ///
+    /// A command executed several times fails when one of its outputs mismatched.
+    pub fn write_junit(&self, path: &Path) -> Result<(), DataError> {
+        // (instruction set index, instruction, commands) in execution order.
+        let mut suites: Vec<(usize, &str, Vec<Vec<&ExpectResult>>)> = Vec::new();
+        for result in &self.expect_results {
+            let suite = match suites.iter().position(|v| v.0 == result.ins_index) {
+                Some(position) => &mut suites[position],
+                None => {
+                    suites.push((result.ins_index, &result.instruction, Vec::new()));
+                    suites.last_mut().unwrap()
+                }
+            };
+            match suite
+                .2
+                .iter_mut()
+                .find(|v| v[0].cmd_index == result.cmd_index)
+            {
+                Some(case) => case.push(result),
+                None => suite.2.push(vec![result]),
+            }
+        }
+
+        let mut report = String::new();
+        let (mut tests, mut failures) = (0, 0);
+        for (ins_index, instruction, cases) in &suites {
+            let suite_name = format!("[{}] {}", ins_index + 1, instruction);
+            let suite_failures = cases.iter().filter(|v| v.iter().any(|v| !v.passed)).count();
+            let _ = writeln!(
+                report,
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">",
+                xml_escape(&suite_name),
+                cases.len(),
+                suite_failures
+            );
+            for case in cases {
+                let name = format!("[{}] {}", case[0].cmd_index + 1, case[0].command);
+                let _ = write!(
+                    report,
+                    "    <testcase name=\"{}\" classname=\"{}\"",
+                    xml_escape(&name),
+                    xml_escape(&suite_name)
+                );
+                match case.iter().find(|v| !v.passed) {
+                    Some(result) => {
+                        let message =
+                            format!("expected {}, found {}", result.expect, result.actual);
+                        let _ = writeln!(
+                            report,
+                            ">\n      <failure message=\"{}\"/>\n    </testcase>",
+                            xml_escape(&message)
+                        );
+                    }
+                    None => {
+                        let _ = writeln!(report, "/>");
+                    }
+                }
+            }
+            let _ = writeln!(report, "  </testsuite>");
+            tests += cases.len();
+            failures += suite_failures;
+        }
+
+        let report = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"rfcaf\" tests=\"{}\" failures=\"{}\">\n{}</testsuites>\n",
+            tests, failures, report
+        );
+        fs::write(path, report)?;
+        Ok(())
+    }
+}
+
+/// Escape the XML special characters of an attribute value.
+fn xml_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -5,16 +5,49 @@
  * @LastEditTime: 2024-08-21 15:50:56
  * @Description:
  */
+pub mod capture_log;
+mod color;
+mod edit;
+pub mod file_log;
 pub mod interface;
-use crate::interface::ConsoleLog;
-use serde_derive::Deserialize;
-use std::io::{self, Write};
-use std::sync::mpsc::Sender;
+#[cfg(feature = "serde-log")]
+pub mod json_log;
+#[cfg(feature = "junit")]
+mod junit;
+#[cfg(feature = "readline")]
+pub mod line_editor;
+pub mod messages;
+pub mod multi_log;
+mod run_log;
+#[cfg(feature = "serial")]
+pub mod serial;
+pub mod tcp;
+mod walk;
+#[cfg(feature = "ws")]
+pub mod ws;
+use crate::color::Paint;
+use crate::interface::{ConsoleInput, ConsoleLog};
+use crate::messages::Messages;
+use crate::run_log::{LogTee, RunLog};
+use crate::walk::CursorWalk;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Lines, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use thiserror::Error;
-use toml;
 
 #[derive(Error, Debug)]
 pub enum DataError {
@@ -22,23 +55,186 @@ pub enum DataError {
     Other(#[from] io::Error), // Convert other error types.
     #[error("{0}")]
     Redaction(String), // error action.
+    #[error("{0}")]
+    Parse(String), // the automatic execution file content is malformed.
+    #[error("{0}")]
+    Corrupted(String), // the imported command set is corrupted, the file needs to be re-imported.
     #[error("invalid header (expected {expected:?}, found {found:?})")]
     InvalidHeader { expected: String, found: String }, // dismatch expect input.
+    #[error("end of input")]
+    Eof, // the terminal input is closed.
+    #[error("file execution is paused")]
+    Paused, // read while the file run is paused.
+    #[error(
+        "command {:?} of instruction set {} reported no result within {timeout_ms} ms",
+        .cmd_index.map(|v| v + 1),
+        .ins_index + 1
+    )]
+    Timeout {
+        ins_index: usize,
+        cmd_index: Option<usize>, // <None for the instruction> command index in the instruction set.
+        timeout_ms: usize,
+    }, // the file command reported no result in time.
+    #[error("文件 {} 导入失败：{source}", .path.display())]
+    File {
+        path: PathBuf,
+        source: Box<DataError>,
+    }, // loading the file of the path failed, its kind is the one of the source.
     #[error("unknown data error")]
     Unknown,
 }
 
+/// Stable category of the `DataError`, for programmatic matching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    Io,             // reading the terminal or a file failed.
+    Parse,          // the automatic execution file content is malformed.
+    InvalidCommand, // the command is rejected, the console keeps running.
+    StateCorrupted, // the imported command set is corrupted, re-import the file.
+    Timeout,        // the command did not finish in time.
+    Paused,         // the file run is paused.
+    Unknown,
+}
+
+impl DataError {
+    /// Category of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            DataError::Other(_) | DataError::Eof => ErrorKind::Io,
+            DataError::Parse(_) => ErrorKind::Parse,
+            DataError::Redaction(_) | DataError::InvalidHeader { .. } => ErrorKind::InvalidCommand,
+            DataError::Corrupted(_) => ErrorKind::StateCorrupted,
+            DataError::Paused => ErrorKind::Paused,
+            DataError::Timeout { .. } => ErrorKind::Timeout,
+            DataError::File { source, .. } => source.kind(),
+            DataError::Unknown => ErrorKind::Unknown,
+        }
+    }
+
+    /// Whether the file run can not continue and the file needs to be re-imported,
+    /// the other errors only reject the current command.
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, DataError::Corrupted(_))
+    }
+}
+
+/// Expand the `${VAR}` variables of the input, looked up in the console variables then the
+/// environment, `${VAR:-fallback}` gives the value used when the variable is undefined.
+fn env_expand(input: &str, vars: &HashMap<String, String>) -> Result<String, DataError> {
+    let mut output = String::new();
+    let mut rest = input;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        output.push_str(&rest[..start]);
+        let token = &rest[start + 2..start + len];
+        let (name, fallback) = match token.split_once(":-") {
+            Some((name, fallback)) => (name, Some(fallback)),
+            None => (token, None),
+        };
+        let value = vars.get(name).cloned().or_else(|| std::env::var(name).ok());
+        match (value, fallback) {
+            (Some(value), _) => output.push_str(&value),
+            (None, Some(fallback)) => output.push_str(fallback),
+            (None, None) => return Err(DataError::Redaction(format!("变量 {} 未定义。", name))),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Characters accepted by the command check beyond the Unicode letters and digits, e.g. the
+/// Chinese device names, and `.+-|@` and the space.
+#[derive(Debug, Clone, Default)]
+pub struct InputPolicy {
+    pub ascii_punctuation: bool, // all ASCII punctuation, e.g. `#:/=,`.
+    pub cjk_punctuation: bool,   // CJK and full-width punctuation, e.g. `，。：（）`.
+    pub extra: Vec<char>,        // additional characters, e.g. an emoji.
+}
+
+impl InputPolicy {
+    fn accepts(&self, c: char) -> bool {
+        c.is_alphanumeric()
+            || matches!(c, '.' | '+' | '-' | '|' | '@' | ' ')
+            || (self.ascii_punctuation && c.is_ascii_punctuation())
+            || (self.cjk_punctuation
+                && matches!(c, '\u{3000}'..='\u{303f}' | '\u{ff00}'..='\u{ffef}'))
+            || self.extra.contains(&c)
+    }
+}
+
+/// Supported automation config formats.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileFormat {
+    Toml, // nested instruction and command assets.
+    List, // one instruction per line, `#` starts a comment line.
+}
+
+impl FileFormat {
+    /// Format of the file of the path, `.txt` and `.list` files are command lists.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|v| v.to_str()) {
+            Some("txt") | Some("list") => FileFormat::List,
+            _ => FileFormat::Toml,
+        }
+    }
+}
+
+/// Cycle times of two merged command sets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CycleMerge {
+    Sum,      // the sum of both cycle times.
+    Max,      // the larger cycle times.
+    KeepLeft, // the cycle times of the merged into command set.
+}
+
+/// Instruction set selected by `Console::run_only`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstructionSelector {
+    Index(usize),  // index of the instruction set in file order.
+    Label(String), // label of the instruction set.
+}
+
 /// Supported file-command data types.
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
-enum GenericCmd {
+pub enum GenericCmd {
     Number(usize),
     Character(String),
 }
 
+impl GenericCmd {
+    /// The command string, with the `${VAR}` variables of characters expanded.
+    fn expand(&self, vars: &HashMap<String, String>) -> Result<String, DataError> {
+        match self {
+            GenericCmd::Character(v) => env_expand(v, vars),
+            GenericCmd::Number(v) => Ok(v.to_string()),
+        }
+    }
+
+    /// Label of the jump command `goto <label>`.
+    fn goto_label(&self) -> Option<&str> {
+        match self {
+            GenericCmd::Character(v) => v.trim().strip_prefix("goto ").map(|v| v.trim()),
+            GenericCmd::Number(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for GenericCmd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenericCmd::Number(v) => write!(f, "{}", v),
+            GenericCmd::Character(v) => write!(f, "{}", v),
+        }
+    }
+}
+
 /// Console Status.
 #[derive(Debug, Clone, PartialEq)]
-enum ConsoleStatus {
+pub enum ConsoleStatus {
     InsAcqFromFile,     // instruction acquisition from file status.
     InsAcqFromTerminal, // instruction acquisition from terminal status.
 
@@ -48,6 +244,20 @@ enum ConsoleStatus {
     Invalid, // invalid state.
 }
 
+/// Handle of a state transition listener, to unsubscribe it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Subscription(usize);
+
+/// Console activity sent on the event channel, see `Console::event_channel`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConsoleEvent {
+    Prompt(String),                            // prompt logged before a read.
+    TerminalExec(String),                      // command read from the terminal.
+    FileExec(String),                          // command read from the file.
+    Error(String),                             // error logged by the console.
+    StateChange(ConsoleStatus, ConsoleStatus), // (previous, current)
+}
+
 #[derive(Deserialize, Debug)]
 struct ValidCheck {
     read_valid: bool,   // Command read valid.
@@ -59,35 +269,531 @@ struct ValidCheck {
 #[derive(Debug)]
 struct ConsolePrompt {
     main_prompt: String,
-    sub_prompt: String,
+    default_prompt: String,  // main prompt restored on each prompt reset.
+    sub_prompt: Vec<String>, // accepted input segments echoed after the main prompt.
+    echo_depth: usize,       // <0 means unlimited> number of recent segments echoed.
+    max_depth: usize,        // <0 means unlimited> number of recent segments kept.
+    elided: bool,            // older segments were dropped beyond the kept depth.
+    breadcrumb: bool, // the file position is rendered instead of the segments during a file run.
 }
 
-/// Automation command execution file config.
-#[derive(Deserialize, Debug)]
-struct ExecuteFile {
+/// Automation command execution file config, loaded from a file or built in code and
+/// installed by `Console::load`.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ExecuteFile {
+    #[serde(skip)]
     file_address: Option<String>, // <populated by file_import> automatic execution command file address.
 
-    exc_ins_assets: Vec<ExecuteAssets>, // <collections> automatically execute instructions and command assets.
-    cycle_times: Option<usize>, // <option, default one time> automatic execution cycle times.
-    mac: Option<String>,
+    pub exc_ins_assets: Vec<ExecuteAssets>, // <collections> automatically execute instructions and command assets.
+    pub cycle_times: Option<usize>, // <option, default one time> automatic execution cycle times.
+    pub mac: Option<String>,
+    pub includes: Option<Vec<String>>, // <option> files whose instruction sets are appended, relative to this file.
+    pub shuffle: Option<bool>, // <option, default false> execute the instruction sets in a random order each cycle.
+
+    #[serde(skip)]
+    ins_cycle_times: Option<usize>, // <populated by file_poll> remaining cycle times of the current instruction set.
+    #[serde(skip)]
+    labels: HashMap<String, usize>, // <populated by file_import> instruction set index of each label.
+    #[serde(skip)]
+    order: Vec<usize>, // <populated by file_import> instruction set index of each position, empty in file order.
 
-    next_exc_ins: Option<(usize, GenericCmd)>, // <populated by file_poll> next automatic execution instruction.
-    next_exc_cmd: Option<(usize, GenericCmd)>, // <populated by file_poll> next auto-execute command.
+    #[serde(skip)]
+    next_exc_ins: Option<usize>, // <populated by file_poll> index of the next automatic execution instruction.
+    #[serde(skip)]
+    next_exc_cmd: Option<usize>, // <populated by file_poll> index of the next auto-execute command.
+    #[serde(skip)]
+    stream: Option<Lines<BufReader<File>>>, // <populated by file_import> remaining lines of the streamed command list.
 }
 
-#[derive(Deserialize, Debug)]
-struct ExecuteAssets {
-    exc_ins: GenericCmd,            // <required> Automatic execution instruction.
-    delay: Option<usize>,           // delay time after the current instruction is completed.
-    t_expect_finish: Option<usize>, // delay time after the current instruction is completed.
-    sub_cmd_assets: Option<Vec<SubCmd>>, // <option> Auto-execute command assets.
+impl ExecuteFile {
+    /// Command set of the instruction sets, executed once in order.
+    pub fn new(exc_ins_assets: Vec<ExecuteAssets>) -> Self {
+        ExecuteFile {
+            exc_ins_assets,
+            ..Default::default()
+        }
+    }
+
+    /// Parse the TOML content of an automation command execution file, the included files
+    /// are not loaded. A malformed content is a `DataError::Redaction` holding the default
+    /// guidance message.
+    pub fn from_toml_str(content: &str) -> Result<ExecuteFile, DataError> {
+        file_parse(content, &Messages::default()).map_err(|err_info| match err_info {
+            DataError::Parse(guidance) => DataError::Redaction(guidance),
+            err_info => err_info,
+        })
+    }
+
+    /// Append the instruction sets of the other command set, like an included file. The labels
+    /// and includes of both are kept, the cycle times are combined by `cycles`, a missing one
+    /// counts as one time.
+    pub fn merge(&mut self, other: ExecuteFile, cycles: CycleMerge) {
+        let (left, right) = (
+            self.cycle_times.unwrap_or(1),
+            other.cycle_times.unwrap_or(1),
+        );
+        self.cycle_times = match cycles {
+            CycleMerge::Sum => Some(left + right),
+            CycleMerge::Max => Some(left.max(right)),
+            CycleMerge::KeepLeft => self.cycle_times,
+        };
+        let offset = self.exc_ins_assets.len();
+        for (label, ins_index) in other.labels {
+            self.labels.entry(label).or_insert(ins_index + offset);
+        }
+        if let Some(includes) = other.includes {
+            self.includes.get_or_insert_with(Vec::new).extend(includes);
+        }
+        self.mac = self.mac.take().or(other.mac);
+        self.shuffle = self.shuffle.or(other.shuffle);
+        self.exc_ins_assets.extend(other.exc_ins_assets);
+        // the shuffled order is rebuilt when the command set is installed.
+        self.order.clear();
+    }
+
+    /// Serialize the command set to the TOML content of an automation command execution file,
+    /// which `from_toml_str` parses back.
+    pub fn to_toml(&self) -> Result<String, DataError> {
+        // through a value, which writes the plain values before the tables.
+        toml::Value::try_from(self)
+            .and_then(|v| toml::to_string(&v))
+            .map_err(|err_info| DataError::Redaction(format!("命令集序列化失败：{}", err_info)))
+    }
+
+    /// Instruction of the instruction set of the index.
+    fn ins_at(&self, ins_index: usize) -> Option<&GenericCmd> {
+        self.exc_ins_assets.get(ins_index).map(|v| &v.exc_ins)
+    }
+
+    /// Command of the index in the instruction set of the index.
+    fn cmd_at(&self, ins_index: usize, cmd_index: usize) -> Option<&GenericCmd> {
+        self.exc_ins_assets
+            .get(ins_index)?
+            .sub_cmd_assets
+            .as_ref()?
+            .get(cmd_index)
+            .map(|v| &v.sub_cmd)
+    }
+
+    /// Expected output of the command of the index in the instruction set of the index.
+    fn expect_at(&self, ins_index: usize, cmd_index: usize) -> Option<&str> {
+        self.exc_ins_assets
+            .get(ins_index)?
+            .sub_cmd_assets
+            .as_ref()?
+            .get(cmd_index)?
+            .expect
+            .as_deref()
+    }
+
+    /// Result timeout of the command of the index in the instruction set of the index,
+    /// or of the instruction itself.
+    fn timeout_at(&self, ins_index: usize, cmd_index: Option<usize>) -> Option<usize> {
+        let exc_assets = self.exc_ins_assets.get(ins_index)?;
+        cmd_index
+            .and_then(|v| exc_assets.sub_cmd_assets.as_ref()?.get(v)?.timeout_ms)
+            .or(exc_assets.timeout_ms)
+    }
+
+    /// Read the next instruction set of the streamed command list into the window,
+    /// false at the end of the list.
+    fn stream_next(&mut self) -> Result<bool, DataError> {
+        let Some(stream) = self.stream.as_mut() else {
+            return Ok(false);
+        };
+        for line in stream {
+            if let Some(exc_assets) = list_line(&line?) {
+                self.exc_ins_assets.clear();
+                self.exc_ins_assets.push(exc_assets);
+                return Ok(true);
+            }
+        }
+        self.stream = None;
+        Ok(false)
+    }
+
+    /// The pending command, or the pending instruction if no command is pending.
+    fn pending(&self) -> Option<&GenericCmd> {
+        let ins_index = self.next_exc_ins?;
+        match self.next_exc_cmd {
+            Some(cmd_index) => self.cmd_at(ins_index, cmd_index),
+            None => self.ins_at(ins_index),
+        }
+    }
 }
 
-#[derive(Deserialize, Debug)]
-struct SubCmd {
-    sub_cmd: GenericCmd,
+/// Instruction set: an instruction and its commands.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExecuteAssets {
+    pub exc_ins: GenericCmd,       // <required> Automatic execution instruction.
+    pub label: Option<String>,     // <option> jump target name of `goto <label>`.
+    pub desc: Option<String>, // <option> human description logged when the instruction set begins.
+    pub tags: Option<Vec<String>>, // <option> tags selected by the tag filter of the console.
+    pub delay: Option<usize>, // delay time after the current instruction is completed.
+    pub delay_jitter: Option<usize>, // <option> random range of the delay, each delay is `delay ± delay_jitter`.
+    pub t_expect_finish: Option<usize>, // delay time after the current instruction is completed.
+    pub cycle_times: Option<usize>, // <option, default one time> cycle times of the sub-command assets.
+    pub skip_if_prev_failed: Option<bool>, // <option, default false> skip when the previous command failed.
+    pub retries: Option<usize>, // <option, default zero> re-issues of a command reported failed, after the delay.
+    pub timeout_ms: Option<usize>, // <option> time to report the result of each command of the instruction set.
+    pub sub_cmd_assets: Option<Vec<SubCmd>>, // <option> Auto-execute command assets.
+}
+
+impl ExecuteAssets {
+    /// Instruction set of a single instruction without commands.
+    pub fn new(exc_ins: GenericCmd) -> Self {
+        ExecuteAssets {
+            exc_ins,
+            label: None,
+            desc: None,
+            tags: None,
+            delay: None,
+            delay_jitter: None,
+            t_expect_finish: None,
+            cycle_times: None,
+            skip_if_prev_failed: None,
+            retries: None,
+            timeout_ms: None,
+            sub_cmd_assets: None,
+        }
+    }
+
+    /// Count of the commands walked in one execution of the instruction set.
+    fn commands_total(&self) -> usize {
+        let sub_cmds = self.sub_cmd_assets.as_deref().unwrap_or(&[]);
+        let ins_cycle_times = self.cycle_times.unwrap_or(1).max(1);
+        match sub_cmds.len() {
+            0 => ins_cycle_times,
+            len => 1 + len * ins_cycle_times,
+        }
+    }
+}
+
+/// Command of an instruction set.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SubCmd {
+    pub sub_cmd: GenericCmd,
+    pub expect: Option<String>, // <option> expected output reported by the embedder through `report_output`.
+    pub timeout_ms: Option<usize>, // <option, default the instruction set timeout> time to report the result.
+}
+
+impl SubCmd {
+    /// Command without expectation and timeout.
+    pub fn new(sub_cmd: GenericCmd) -> Self {
+        SubCmd {
+            sub_cmd,
+            expect: None,
+            timeout_ms: None,
+        }
+    }
+}
+
+/// Saved execution progress of a file run, used to resume it after a re-import.
+#[derive(Serialize, Deserialize, Debug)]
+struct ExecuteProgress {
+    next_exc_ins: Option<usize>, // index of the next automatic execution instruction.
+    next_exc_cmd: Option<usize>, // index of the next auto-execute command.
+    cycle_times: Option<usize>,  // remaining automatic execution cycle times.
+    ins_cycle_times: Option<usize>, // remaining cycle times of the current instruction set.
+    #[serde(default)]
+    order: Vec<usize>, // instruction set index of each position of the shuffled cycle.
+}
+
+/// Parse the automation command execution file content.
+fn file_parse(context: &str, messages: &Messages) -> Result<ExecuteFile, DataError> {
+    match toml::from_str::<ExecuteFile>(context) {
+        Ok(v) => Ok(v),
+        Err(_err_info) => Err(DataError::Parse(messages.file_malformed.to_string())),
+    }
+}
+
+/// Instruction set of the command list line, None for empty and `#` comment lines.
+fn list_line(line: &str) -> Option<ExecuteAssets> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    Some(ExecuteAssets::new(GenericCmd::Character(line.to_string())))
+}
+
+/// Parse the command list content, each non-empty line is one instruction.
+fn list_parse(context: &str) -> ExecuteFile {
+    let exc_ins_assets = context.lines().filter_map(list_line).collect();
+    ExecuteFile {
+        exc_ins_assets,
+        ..Default::default()
+    }
+}
+
+/// Load the automation command execution file of the path, appending the instruction sets
+/// of its included files in order. `stack` holds the files being loaded to detect cyclic includes.
+fn file_load(
+    path: &Path,
+    stack: &mut Vec<PathBuf>,
+    messages: &Messages,
+) -> Result<ExecuteFile, DataError> {
+    let canonical = std::fs::canonicalize(path)?;
+    if stack.contains(&canonical) {
+        return Err(DataError::Parse(Messages::fill(
+            messages.file_cyclic_include,
+            &[&path.display()],
+        )));
+    }
+    stack.push(canonical);
+
+    let context = std::fs::read_to_string(path)?;
+    let exc_file = content_load(
+        &context,
+        FileFormat::from_path(path),
+        path.parent().unwrap_or(Path::new("")),
+        stack,
+        messages,
+    )?;
+
+    stack.pop();
+    Ok(exc_file)
+}
+
+/// Load the automation command execution file content, the included files are relative to `base`.
+fn content_load(
+    context: &str,
+    format: FileFormat,
+    base: &Path,
+    stack: &mut Vec<PathBuf>,
+    messages: &Messages,
+) -> Result<ExecuteFile, DataError> {
+    let exc_file = match format {
+        FileFormat::Toml => file_parse(context, messages)?,
+        FileFormat::List => list_parse(context),
+    };
+    includes_load(exc_file, base, stack, messages)
+}
+
+/// Append the instruction sets of the included files, relative to `base`.
+fn includes_load(
+    mut exc_file: ExecuteFile,
+    base: &Path,
+    stack: &mut Vec<PathBuf>,
+    messages: &Messages,
+) -> Result<ExecuteFile, DataError> {
+    for include in exc_file.includes.take().unwrap_or_default() {
+        let included = file_load(&base.join(include), stack, messages)?;
+        exc_file.exc_ins_assets.extend(included.exc_ins_assets);
+    }
+    Ok(exc_file)
+}
+
+/// Load the automation command execution file of the path and index its labels.
+fn file_prepare(path: &Path, messages: &Messages) -> Result<ExecuteFile, DataError> {
+    label_index(file_load(path, &mut Vec::new(), messages)?, messages)
+}
+
+/// Index the labels of the loaded automation command execution file.
+fn label_index(mut exc_file: ExecuteFile, messages: &Messages) -> Result<ExecuteFile, DataError> {
+    let (labels, errors) = label_check(&exc_file.exc_ins_assets, messages);
+    if let Some(err_info) = errors.into_iter().next() {
+        return Err(err_info);
+    }
+    exc_file.labels = labels;
+    Ok(exc_file)
+}
+
+/// Build the label index map of the instruction sets, and report the duplicate labels
+/// and the jumps to undefined labels.
+fn label_check(
+    exc_ins_assets: &[ExecuteAssets],
+    messages: &Messages,
+) -> (HashMap<String, usize>, Vec<DataError>) {
+    let mut labels = HashMap::new();
+    let mut errors = Vec::new();
+    for (ins_index, exc_assets) in exc_ins_assets.iter().enumerate() {
+        if let Some(label) = &exc_assets.label {
+            if labels.insert(label.clone(), ins_index).is_some() {
+                errors.push(DataError::Parse(Messages::fill(
+                    messages.label_duplicate,
+                    &[&(ins_index + 1), label],
+                )));
+            }
+        }
+    }
+    for (ins_index, exc_assets) in exc_ins_assets.iter().enumerate() {
+        let sub_cmds = exc_assets.sub_cmd_assets.iter().flatten();
+        for cmd in std::iter::once(&exc_assets.exc_ins).chain(sub_cmds.map(|v| &v.sub_cmd)) {
+            if let Some(label) = cmd.goto_label() {
+                if !labels.contains_key(label) {
+                    errors.push(DataError::Parse(Messages::fill(
+                        messages.label_undefined,
+                        &[&(ins_index + 1), &label],
+                    )));
+                }
+            }
+        }
+    }
+    (labels, errors)
+}
+
+/// Upper bound of the automatic execution cycle times accepted by validation.
+const CYCLE_TIMES_MAX: usize = 100_000;
+
+/// Dry-parse an automation file content and report every semantic problem found.
+///
+/// A structural parse failure is reported alone, since nothing further can be checked.
+pub fn validate_collecting(content: &str) -> Vec<DataError> {
+    let exc_file = match toml::from_str::<ExecuteFile>(content) {
+        Ok(v) => v,
+        Err(err_info) => return vec![DataError::Parse(format!("文件内容格式有误：{}", err_info))],
+    };
+
+    let mut errors = Vec::new();
+    if exc_file.exc_ins_assets.is_empty() {
+        errors.push(DataError::Redaction(
+            Messages::default().file_empty.to_string(),
+        ));
+    }
+    match exc_file.cycle_times {
+        Some(0) => errors.push(DataError::Redaction("执行次数不能为 0。".to_string())),
+        Some(cycle_times) if cycle_times > CYCLE_TIMES_MAX => errors.push(DataError::Redaction(
+            format!("执行次数 {} 超出上限 {}。", cycle_times, CYCLE_TIMES_MAX),
+        )),
+        _ => {}
+    }
+    for (ins_index, exc_assets) in exc_file.exc_ins_assets.iter().enumerate() {
+        if let Some(sub_cmd_assets) = &exc_assets.sub_cmd_assets {
+            if sub_cmd_assets.is_empty() {
+                errors.push(DataError::Redaction(format!(
+                    "第 {} 条主指令集的子命令集为空。",
+                    ins_index + 1
+                )));
+            }
+        }
+        match exc_assets.cycle_times {
+            Some(0) => errors.push(DataError::Redaction(format!(
+                "第 {} 条主指令集的执行次数不能为 0。",
+                ins_index + 1
+            ))),
+            Some(cycle_times) if cycle_times > CYCLE_TIMES_MAX => {
+                errors.push(DataError::Redaction(format!(
+                    "第 {} 条主指令集的执行次数 {} 超出上限 {}。",
+                    ins_index + 1,
+                    cycle_times,
+                    CYCLE_TIMES_MAX
+                )))
+            }
+            _ => {}
+        }
+    }
+    errors.extend(label_check(&exc_file.exc_ins_assets, &Messages::default()).1);
+
+    errors
+}
+
+/// Key metrics of a completed file run.
+#[derive(Debug, Clone, Default)]
+pub struct RunSummary {
+    pub commands: usize,              // commands read from the file.
+    pub failures: usize,              // invalid commands and failures reported by the embedder.
+    pub duration: Duration,           // time from the file import to the last command.
+    pub passed: usize,                // reported outputs matching the expected output.
+    pub failed: usize,                // reported outputs mismatching the expected output.
+    pub failing: Vec<(usize, usize)>, // (instruction set, command) index of the mismatched commands.
+    pub retries: usize,               // commands re-issued after a reported failure.
+}
+
+/// Last command read from the file, re-issued when it is reported failed.
+#[derive(Debug)]
+struct Retry {
+    cmd: String,
+    left: usize,                  // remaining re-issues.
+    delay: usize,                 // delay time before each re-issue.
+    pending: bool,                // reported failed, re-issued by the next read.
+    served: Option<ExpectResult>, // expected output of the command.
+    deadline: Option<Deadline>,   // result timeout of the command.
+}
+
+/// Result timeout of the last command read from the file, checked when the result is reported
+/// or on the next read.
+#[derive(Debug, Clone)]
+struct Deadline {
+    start: Instant,
+    timeout_ms: usize,
+    ins_index: usize,
+    cmd_index: Option<usize>,
+}
+
+impl Deadline {
+    fn expired(&self) -> bool {
+        self.start.elapsed() > Duration::from_millis(self.timeout_ms as u64)
+    }
+
+    fn error(&self) -> DataError {
+        DataError::Timeout {
+            ins_index: self.ins_index,
+            cmd_index: self.cmd_index,
+            timeout_ms: self.timeout_ms,
+        }
+    }
+}
+
+/// Comparison of the reported output of a file command with its expected output.
+#[derive(Debug, Clone)]
+pub struct ExpectResult {
+    pub ins_index: usize, // instruction set index of the command.
+    pub cmd_index: usize, // command index in the instruction set.
+    pub instruction: String,
+    pub command: String,
+    pub expect: String,
+    pub actual: String,
+    pub passed: bool,
+}
+
+/// Watcher of the imported file, reloading it when it changes on disk.
+#[derive(Debug)]
+struct FileWatch {
+    path: PathBuf,                // canonical path of the watched file.
+    _watcher: RecommendedWatcher, // keeps the watch alive.
+    events: Receiver<notify::Result<Event>>,
 }
 
+/// Callback of the final command of each read.
+type Postprocessor = Box<dyn FnMut(&str)>;
+type StatusListener = Box<dyn FnMut(ConsoleStatus, ConsoleStatus)>;
+
+/// Callbacks registered by the embedder.
+#[derive(Default)]
+struct Hooks {
+    transition: Option<Box<dyn FnMut(ConsoleStatus, ConsoleStatus)>>, // (previous, current)
+    prompt: Option<Box<dyn Fn() -> String>>, // main prompt computed on each read.
+    preprocessor: Option<Box<dyn Fn(String) -> String>>, // transform of every parsed command.
+    postprocessor: Option<Postprocessor>,    // called with every command read.
+    listeners: Vec<(Subscription, StatusListener)>, // state transition listeners in registration order.
+    next_subscription: usize,
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hooks")
+            .field("transition", &self.transition.is_some())
+            .field("prompt", &self.prompt.is_some())
+            .field("preprocessor", &self.preprocessor.is_some())
+            .field("postprocessor", &self.postprocessor.is_some())
+            .field("listeners", &self.listeners.len())
+            .finish()
+    }
+}
+
+/// Number of the recent state transitions kept by the console.
+const TRANSITION_HISTORY_MAX: usize = 32;
+
+/// Number of the recent command timings kept by the console.
+const TIMINGS_MAX: usize = 64;
+
+/// Bytes of the body read by `read_block`.
+const BLOCK_LEN_MAX: usize = 64 * 1024;
+
+/// A second interrupt within this window exits the process.
+#[cfg(feature = "interrupt")]
+const INTERRUPT_EXIT_WINDOW: Duration = Duration::from_secs(2);
+
 #[derive(Debug)]
 struct Status {
     current: ConsoleStatus,
@@ -106,9 +812,63 @@ where
 
     interact: ConsolePrompt,
     log: Arc<Mutex<T>>,
+    input: Box<dyn ConsoleInput>, // source of the terminal commands, the line editor with the `readline` feature.
     pub _input_invalid: &'static str,
 
     auto_exc: ExecuteFile,
+    group_only: bool, // instruction sets without commands are only grouping nodes.
+    tag_filter: HashSet<String>, // <empty executes all> tags of the executed instruction sets.
+    run_only: Option<usize>, // <populated by run_only> the only instruction set executed by the file run.
+    skip_ranges: Vec<Range<usize>>, // <populated by set_skip_ranges> instruction set indexes skipped by the file run.
+    last_result: bool,              // result of the last executed command reported by the embedder.
+    summary: RunSummary,
+    run_start: Option<Instant>, // start time of the file run in progress.
+    watch: Option<FileWatch>,
+    run_log_dir: Option<PathBuf>, // <option> directory of the log files of the file runs.
+    run_log: Option<RunLog>, // <populated by the first file read> log file of the file run in progress.
+    rng: StdRng, // random source of the shuffled instruction order and the delay jitter.
+    delay_jitter: Option<Duration>, // <option> random range of every delay, instead of the file ones.
+    messages: Messages,
+    color: bool,          // wrap the prompt and error output in ANSI colors.
+    log_timestamps: bool, // prepend the timestamp to the prompt and command log information.
+    quiet: bool,          // the prompts and the state transition notices are not logged.
+    verbose: bool,        // the file cursor moves and the state refreshes are logged.
+    history: VecDeque<(ConsoleStatus, ConsoleStatus)>, // recent (previous, current) state transitions.
+    paused: bool, // the file run keeps its progress and reads nothing until resumed.
+    breakpoints: HashSet<usize>, // instruction set indexes pausing the file run before they execute.
+    breakpoint_hit: Option<usize>, // <populated by read> instruction set of the breakpoint paused at.
+    list_streaming: bool,          // command list files are read line by line during the run.
+    batch_error: Option<DataError>, // <populated by read_batch> error of a partial batch, returned by the next batch.
+    step_mode: bool,                // each file command waits for `step` or the terminal enter.
+    step_wait: bool, // a file command has been read in step mode and the next one waits.
+    stop_on_error: bool, // a rejected command stops `run_to_completion`.
+    hooks: Hooks,
+    events: Option<Sender<ConsoleEvent>>, // <populated by event_channel> sender of the console activity.
+    served: Option<ExpectResult>, // <output populated by report_output> last command read from the file with an expected output.
+    expect_results: Vec<ExpectResult>, // results of the reported outputs of the file run.
+    strict_expect: bool,          // an output mismatch aborts the file run.
+    retry: Option<Retry>, // <populated by file_read> retry of the last command read from the file.
+    deadline: Option<Deadline>, // <populated by file_read> result timeout of the last command read from the file.
+    aliases: HashMap<String, String>, // expansion of each alias of the first word of a command.
+    file_aliases: bool,         // the aliases are expanded in the file commands too.
+    vars: HashMap<String, String>, // values of the `${var}` of the file commands, before the environment.
+    rate_interval: Option<Duration>, // <option> minimum interval between two commands.
+    rate_last: Option<Instant>,    // time the previous command was returned.
+    timing: Option<(String, Instant)>, // last command read, and the start of its read.
+    read_input: Option<String>, // <populated by the terminal and file read> command of the current read.
+    timings: VecDeque<(String, Duration)>, // recent commands and their time until the next read.
+    command_counts: (usize, usize), // (terminal, file) commands read, the file ones since the import.
+    allowed: Option<HashSet<String>>, // <option> the only commands executed.
+    denied: HashSet<String>,        // commands never executed, before the allowed ones.
+    commands: BTreeMap<String, Option<String>>, // <populated by register_command> (name, description) listed by help.
+    input_policy: InputPolicy,
+    trim_chars: Vec<char>, // <default `\r` and `\n`> characters trimmed from the end of each input.
+    normalize_crlf: bool,  // the CRLF inside the input are normalized to LF.
+    choice_ignore_case: bool, // `read_choice` matches the choices in any case.
+    comment: Option<char>, // <option> delimiter of the inline comments of the file commands.
+    interrupted: Arc<AtomicBool>, // set by the interrupt handler, the file run is aborted on the next read.
+    progress_total: usize, // <populated by file_import> commands of the file run, 0 for a streamed list.
+    expect_pending: bool, // the finished run waits for the output of its last command before its expectation summary.
 
     current_ins: Option<String>, // currently executing instruction.
     current_cmd: Option<String>, // currently executing command.
@@ -119,14 +879,10 @@ where
     T: ConsoleLog,
 {
     pub fn new(log: Arc<Mutex<T>>) -> Self {
-        let invalid_info = match log.lock().map_err(|_| {
-            DataError::Redaction("log information prints mutex acquisition failure.".to_string())
-        }) {
-            Ok(log) => log.err_invalid(),
-            Err(_err_info) => {
-                panic!("{}", _err_info);
-            }
-        };
+        let invalid_info = log
+            .lock()
+            .unwrap_or_else(|err_info| err_info.into_inner())
+            .err_invalid();
 
         Console {
             status: Status {
@@ -143,25 +899,193 @@ where
 
             interact: ConsolePrompt {
                 main_prompt: String::from("> "),
-                sub_prompt: String::from(""),
+                default_prompt: String::from("> "),
+                sub_prompt: Vec::new(),
+                echo_depth: 0,
+                max_depth: 0,
+                elided: false,
+                breadcrumb: false,
             },
             log,
+            #[cfg(feature = "readline")]
+            input: Box::new(line_editor::LineEditor::new()),
+            #[cfg(not(feature = "readline"))]
+            input: Box::new(io::stdin()),
             _input_invalid: invalid_info,
 
-            auto_exc: ExecuteFile {
-                file_address: None,
-                exc_ins_assets: Vec::new(),
-                cycle_times: None,
-                mac: None,
-                next_exc_ins: None,
-                next_exc_cmd: None,
-            },
+            auto_exc: ExecuteFile::default(),
+            group_only: false,
+            tag_filter: HashSet::new(),
+            run_only: None,
+            skip_ranges: Vec::new(),
+            last_result: true,
+            summary: RunSummary::default(),
+            run_start: None,
+            rng: StdRng::from_os_rng(),
+            delay_jitter: None,
+            messages: Messages::default(),
+            color: color::color_default(),
+            log_timestamps: true,
+            quiet: false,
+            verbose: false,
+            history: VecDeque::with_capacity(TRANSITION_HISTORY_MAX),
+            paused: false,
+            breakpoints: HashSet::new(),
+            breakpoint_hit: None,
+            list_streaming: false,
+            batch_error: None,
+            step_mode: false,
+            stop_on_error: false,
+            step_wait: false,
+            hooks: Hooks::default(),
+            events: None,
+            served: None,
+            expect_results: Vec::new(),
+            strict_expect: false,
+            expect_pending: false,
+            retry: None,
+            deadline: None,
+            aliases: HashMap::new(),
+            file_aliases: false,
+            comment: None,
+            choice_ignore_case: false,
+            rate_interval: None,
+            rate_last: None,
+            timing: None,
+            read_input: None,
+            timings: VecDeque::with_capacity(TIMINGS_MAX),
+            command_counts: (0, 0),
+            allowed: None,
+            denied: HashSet::new(),
+            commands: BTreeMap::new(),
+            input_policy: InputPolicy::default(),
+            trim_chars: vec!['\r', '\n'],
+            normalize_crlf: false,
+            vars: HashMap::new(),
+            interrupted: Arc::new(AtomicBool::new(false)),
+            progress_total: 0,
+            watch: None,
+            run_log_dir: None,
+            run_log: None,
 
             current_ins: None,
             current_cmd: None,
         }
     }
 
+    /// Replace the user-facing text of the console, e.g. `Console::new(log).messages(..)`.
+    pub fn messages(mut self, messages: Messages) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    /// Replace the default main prompt `> `, e.g. `Console::new(log).prompt("rfcaf> ")`, it is
+    /// restored after each command.
+    pub fn prompt(mut self, prompt: &str) -> Self {
+        self.interact.main_prompt = prompt.to_string();
+        self.interact.default_prompt = prompt.to_string();
+        self
+    }
+
+    /// Enable or disable the colored output, which overrides the `NO_COLOR` environment variable.
+    #[cfg(feature = "colored")]
+    pub fn set_color(&mut self, color: bool) {
+        self.color = color;
+    }
+
+    /// Enable or disable the timestamp of the prompt and command log information, enabled by default.
+    pub fn set_log_timestamps(&mut self, log_timestamps: bool) {
+        self.log_timestamps = log_timestamps;
+    }
+
+    /// Suppress the prompts and the state transition notices, e.g. for headless runs. The errors,
+    /// the command execution log information, the `help` answer and the `select` options are
+    /// still logged, the transition callback is still invoked.
+    pub fn set_quiet(&mut self, quiet: bool) {
+        self.quiet = quiet;
+    }
+
+    /// Write the log information of each file run to its own `run-<timestamp>.log` file in the
+    /// directory as well, the file is closed once the run completes. `None` disables it.
+    pub fn set_run_log_dir(&mut self, dir: Option<PathBuf>) {
+        self.run_log_dir = dir;
+    }
+
+    /// Log every file cursor move and state refresh through `debug_log`, for diagnosing the
+    /// cycles and jumps of a file.
+    pub fn set_verbose(&mut self, verbose: bool) {
+        self.verbose = verbose;
+    }
+
+    /// Register the callback invoked with (previous, current) after every console state transition.
+    pub fn on_transition(&mut self, f: impl FnMut(ConsoleStatus, ConsoleStatus) + 'static) {
+        self.hooks.transition = Some(Box::new(f));
+    }
+
+    /// Register a listener invoked with (previous, current) after every console state transition,
+    /// after the `on_transition` callback and the listeners registered before it.
+    pub fn subscribe_status(
+        &mut self,
+        f: impl FnMut(ConsoleStatus, ConsoleStatus) + 'static,
+    ) -> Subscription {
+        let subscription = Subscription(self.hooks.next_subscription);
+        self.hooks.next_subscription += 1;
+        self.hooks.listeners.push((subscription, Box::new(f)));
+        subscription
+    }
+
+    /// Remove the listener of the subscription, false if it is already removed.
+    pub fn unsubscribe_status(&mut self, subscription: Subscription) -> bool {
+        let len = self.hooks.listeners.len();
+        self.hooks.listeners.retain(|(v, _)| *v != subscription);
+        self.hooks.listeners.len() != len
+    }
+
+    /// Channel of the console activity, e.g. to render it from another thread. A new channel
+    /// replaces the previous one, the events are dropped once the receiver is dropped.
+    pub fn event_channel(&mut self) -> Receiver<ConsoleEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.events = Some(sender);
+        receiver
+    }
+
+    /// Register the callback computing the main prompt on each read, e.g. from the connected
+    /// device. The accepted input segments are still echoed after it.
+    pub fn set_prompt_fn(&mut self, f: impl Fn() -> String + 'static) {
+        self.hooks.prompt = Some(Box::new(f));
+    }
+
+    /// Register the transform applied to every command read from the terminal and the file,
+    /// e.g. to uppercase it or prepend a prefix. The transforms apply in this order:
+    ///
+    /// 1. the variable substitution of the file commands;
+    /// 2. the input parser and the comment stripping of the file commands;
+    /// 3. the alias expansion;
+    /// 4. the preprocessor, its output is then checked like any input.
+    pub fn set_preprocessor(&mut self, f: impl Fn(String) -> String + 'static) {
+        self.hooks.preprocessor = Some(Box::new(f));
+    }
+
+    /// Register the callback called at the end of each read with the final command, after it
+    /// is logged, e.g. to forward it to an audit service. It is also called when the command
+    /// is rejected, but not when the read fails before a command is read.
+    pub fn set_postprocessor(&mut self, f: impl FnMut(&str) + 'static) {
+        self.hooks.postprocessor = Some(Box::new(f));
+    }
+
+    /// Apply the preprocessor to the command.
+    fn preprocess(&self, input: String) -> String {
+        match &self.hooks.preprocessor {
+            Some(preprocessor) => preprocessor(input),
+            None => input,
+        }
+    }
+
+    /// Current console state.
+    pub fn current_status(&self) -> ConsoleStatus {
+        self.status.current.clone()
+    }
+
     /// initialize after creating the console object to refresh the state machine.
     pub fn setup(&mut self) {
         let _ = self.refresh();
@@ -173,31 +1097,22 @@ where
             // during the automated execution, no action is required.
             ConsoleStatus::InsAcqFromFile => {
                 if 0 != self.delay.1 {
-                    match self.log.lock().map_err(|_| {
-                        DataError::Redaction(
-                            "log information prints mutex acquisition failure.".to_string(),
-                        )
-                    }) {
-                        Ok(log) => log.file_exc_log(&format!(" * 延时等待 {} ms", self.delay.1)),
-                        Err(_err_info) => {
-                            panic!("{}", _err_info);
-                        }
-                    }
+                    self.file_log(&format!(" * 延时等待 {} ms", self.delay.1));
 
                     thread::sleep(Duration::from_millis(self.delay.1 as u64));
                 }
-                self.t_expect_finish = (self.t_expect_finish.1, self.t_expect_finish.1, self.t_expect_finish.2);
-                return;
+                self.t_expect_finish = (
+                    self.t_expect_finish.1,
+                    self.t_expect_finish.1,
+                    self.t_expect_finish.2,
+                );
             }
             // during the automated execution, no action is required.
             ConsoleStatus::InsExecFromFile => {
                 // this branch does not exist in theory
-                return;
             }
             // this is the expected state, no action is required.
-            ConsoleStatus::InsAcqFromTerminal => {
-                return;
-            }
+            ConsoleStatus::InsAcqFromTerminal => {}
             // constrain the unexpected invalid state and return to the terminal to obtain the instruction state.
             ConsoleStatus::Invalid => {
                 let _ = self.refresh();
@@ -212,23 +1127,33 @@ where
 
     /// input character parser.
     fn input_parser(&self, input: String) -> String {
-        let x: &[_] = &['\r', '\n'];
-        return String::from(input.trim_end_matches(x));
+        let input = input.trim_end_matches(self.trim_chars.as_slice());
+        match self.normalize_crlf {
+            true => input.replace("\r\n", "\n"),
+            false => String::from(input),
+        }
+    }
+
+    /// Strip the inline comment of the file command, from the unquoted delimiter to the end.
+    fn comment_strip(&self, input: String) -> String {
+        let Some(delimiter) = self.comment else {
+            return input;
+        };
+        let mut quote = None;
+        for (index, c) in input.char_indices() {
+            match (quote, c) {
+                (None, '"' | '\'') => quote = Some(c),
+                (Some(open), _) if open == c => quote = None,
+                (None, _) if c == delimiter => return input[..index].trim_end().to_string(),
+                _ => {}
+            }
+        }
+        input
     }
 
     /// input character check.
     fn input_check(&mut self, input: &str) -> Result<bool, DataError> {
-        // if !input.chars().all(|c| {
-        //     c.is_alphanumeric()
-        //         || c == '.'
-        //         || c == '+'
-        //         || c == '-'
-        //         || c == '|'
-        //         || c == '@'
-        //         || c == ' '
-        // }) || input == "".to_string()
-        if true
-        {
+        if !input.chars().all(|c| self.input_policy.accepts(c)) || input.is_empty() {
             Err(DataError::InvalidHeader {
                 expected: ("specified command characters".to_string()),
                 found: ("invalid characters".to_string()),
@@ -238,20 +1163,42 @@ where
         }
     }
 
+    /// Reject the command denied, or not allowed, by its full text or its first word.
+    fn command_permit(&self, input: &str) -> Result<(), DataError> {
+        let word = input.split_whitespace().next().unwrap_or_default();
+        let listed = |set: &HashSet<String>| set.contains(input) || set.contains(word);
+        if listed(&self.denied) || self.allowed.as_ref().is_some_and(|v| !listed(v)) {
+            return Err(DataError::Redaction(Messages::fill(
+                self.messages.command_denied,
+                &[&input],
+            )));
+        }
+        Ok(())
+    }
+
     /// get instructions from the terminal.
-    fn terminal_read(&mut self, _prompt: &str) -> Result<String, DataError> {
-        let _ = io::stdout().flush();
-        let mut input = String::new();
-        io::stdin()
-            .read_line(&mut input)
-            .map_err(|_| DataError::InvalidHeader {
-                expected: ("terminal input".to_string()),
-                found: ("invalid input".to_string()),
-            })?;
+    fn terminal_read(&mut self, prompt: &str) -> Result<String, DataError> {
+        self.check.read_valid = false;
+        let input = loop {
+            let _ = io::stdout().flush();
+            let mut input = String::new();
+            if self.input.read_line(&mut input)? == 0 {
+                return Err(DataError::Eof);
+            }
 
-        // input parser and check.
-        input = self.input_parser(input);
-        self.check.read_valid = self.input_check(&input)?;
+            // input parser and check.
+            input = self.preprocess(self.alias_expand(self.input_parser(input)));
+            self.read_input = Some(input.clone());
+            self.input_check(&input)?;
+
+            // the built-in help is answered here and the terminal is read again.
+            if !self.help(&input) {
+                break input;
+            }
+            self.prompt_show(prompt);
+        };
+        self.command_permit(&input)?;
+        self.check.read_valid = true;
 
         // input valid and apply it.
         if let ConsoleStatus::InsAcqFromTerminal = self.status.current {
@@ -259,45 +1206,102 @@ where
         } else {
             self.current_cmd = Some(input.clone());
         }
-        self.interact
-            .sub_prompt
-            .push_str(&format!("{} > ", input.clone()));
+        self.prompt_push(input.clone());
 
         // terminal command execution output.
-        match self.log.lock().map_err(|_| {
-            DataError::Redaction("log information prints mutex acquisition failure.".to_string())
-        }) {
-            Ok(log) => log.terminal_exc_log(&input),
-            Err(_err_info) => {
-                panic!("{}", _err_info);
-            }
-        }
+        self.terminal_log(&input);
+        self.event_send(ConsoleEvent::TerminalExec(input.clone()));
+        self.command_counts.0 += 1;
 
         Ok(input)
     }
 
     /// Get instructions from the file.
     fn file_read(&mut self, _prompt: &str) -> Result<String, DataError> {
-        let mut input = if let Some((_, input)) =
-            if let ConsoleStatus::InsAcqFromFile = self.status.current {
-                self.auto_exc.next_exc_ins.clone()
-            } else {
-                self.auto_exc.next_exc_cmd.clone()
-            } {
-            match input {
-                GenericCmd::Character(v) => v,
-                GenericCmd::Number(v) => v.to_string(),
+        self.served = None;
+        let cmd = match (
+            &self.status.current,
+            self.auto_exc.next_exc_ins,
+            self.auto_exc.next_exc_cmd,
+        ) {
+            (ConsoleStatus::InsAcqFromFile, Some(ins_index), _) => self.auto_exc.ins_at(ins_index),
+            (ConsoleStatus::InsExecFromFile, Some(ins_index), Some(cmd_index)) => {
+                let cmd = self.auto_exc.cmd_at(ins_index, cmd_index);
+                self.served =
+                    self.auto_exc
+                        .expect_at(ins_index, cmd_index)
+                        .map(|expect| ExpectResult {
+                            ins_index,
+                            cmd_index,
+                            instruction: self
+                                .auto_exc
+                                .ins_at(ins_index)
+                                .map(|v| v.to_string())
+                                .unwrap_or_default(),
+                            command: cmd.map(|v| v.to_string()).unwrap_or_default(),
+                            expect: expect.to_string(),
+                            actual: String::new(),
+                            passed: false,
+                        });
+                cmd
             }
-        } else {
+            _ => None,
+        };
+        let Some(cmd) = cmd.map(|v| v.expand(&self.vars)) else {
             return Err(DataError::Redaction(
                 "no executable instructions or commands.".to_string(),
             ));
         };
+        let (retries, delay) = match self
+            .auto_exc
+            .next_exc_ins
+            .and_then(|v| self.auto_exc.exc_ins_assets.get(v))
+        {
+            Some(exc_assets) => (
+                exc_assets.retries.unwrap_or(0),
+                exc_assets.delay.unwrap_or(0),
+            ),
+            None => (0, 0),
+        };
+        let cmd_index = match self.status.current {
+            ConsoleStatus::InsExecFromFile => self.auto_exc.next_exc_cmd,
+            _ => None,
+        };
+        self.deadline = self.auto_exc.next_exc_ins.and_then(|ins_index| {
+            Some(Deadline {
+                start: Instant::now(),
+                timeout_ms: self.auto_exc.timeout_at(ins_index, cmd_index)?,
+                ins_index,
+                cmd_index,
+            })
+        });
+        let desc = match (&self.status.current, self.auto_exc.next_exc_ins) {
+            (ConsoleStatus::InsAcqFromFile, Some(ins_index)) => self.auto_exc.exc_ins_assets
+                [ins_index]
+                .desc
+                .clone()
+                .map(|desc| (ins_index, desc)),
+            _ => None,
+        };
         let _ = self.file_poll();
 
         // input parser and check.
-        input = self.input_parser(input);
+        let mut input = self.comment_strip(self.input_parser(cmd?));
+        if self.file_aliases {
+            input = self.alias_expand(input);
+        }
+        input = self.preprocess(input);
+        self.read_input = Some(input.clone());
         self.check.read_valid = self.input_check(&input)?;
+        self.command_permit(&input)?;
+        self.retry = (retries != 0).then(|| Retry {
+            cmd: input.clone(),
+            left: retries,
+            delay,
+            pending: false,
+            served: self.served.clone(),
+            deadline: self.deadline.clone(),
+        });
 
         // input valid and apply it.
         if let ConsoleStatus::InsAcqFromTerminal = self.status.current {
@@ -305,19 +1309,15 @@ where
         } else {
             self.current_cmd = Some(input.clone());
         }
-        self.interact
-            .main_prompt
-            .push_str(&format!("{} > ", input.clone()));
+        self.prompt_push(input.clone());
 
         // automatic file command execution output.
-        match self.log.lock().map_err(|_| {
-            DataError::Redaction("log information prints mutex acquisition failure.".to_string())
-        }) {
-            Ok(log) => log.file_exc_log(&input),
-            Err(_err_info) => {
-                panic!("{}", _err_info);
-            }
+        if let Some((ins_index, desc)) = desc {
+            self.log_lock().desc_log(ins_index, &desc);
         }
+        self.file_log(&input);
+        self.event_send(ConsoleEvent::FileExec(input.clone()));
+        self.command_counts.1 += 1;
 
         Ok(input)
     }
@@ -326,322 +1326,600 @@ where
         // clear the saved command set.
         Console::exc_clear(self);
 
-        self.auto_exc.file_address = Some(self.read("请输入文件地址")?);
+        let file_address = self.read(self.messages.file_address)?;
         self.check.read_valid = true; // need re-set in file_import.
         self.check.file_valid = true;
-        let context = std::fs::read_to_string(&self.auto_exc.file_address.clone().unwrap())?;
-        self.auto_exc = match toml::from_str::<ExecuteFile>(&context) {
-            Ok(v) => v,
-            Err(_err_info) => {
-                return Err(DataError::Redaction(format!(
-                    "{} {}  {}  {}",
-                    "文件内容格式有误，检查文件内容是否满足：",
-                    "- 文件涉及测试组 执行次数 <可选，若未输入默认执行一次>",
-                    "- 单次测试 主指令 <必须>",
-                    "- 单次测试 子命令/子命令集 <可选>"
-                )));
-            }
-        };
-
-        // pre-population.
-        self.file_poll()?;
-        self.check.import_valid = true;
-        // println!("{:#?}", self.auto_exc);
+        let path = Path::new(&file_address);
+        let mut exc_file =
+            match self.list_streaming && FileFormat::from_path(path) == FileFormat::List {
+                true => ExecuteFile {
+                    stream: Some(BufReader::new(File::open(path)?).lines()),
+                    ..Default::default()
+                },
+                false => file_prepare(path, &self.messages)?,
+            };
+        exc_file.file_address = Some(file_address);
 
-        self.refresh()?;
-        Ok(())
+        self.exc_install(exc_file)
     }
 
-    pub fn file_import_no_err(&mut self) {
+    /// Import the automation config from the content instead of a file,
+    /// the included files are relative to the current directory.
+    pub fn import_from_str(&mut self, content: &str, format: FileFormat) -> Result<(), DataError> {
+        // clear the saved command set.
+        Console::exc_clear(self);
+
+        let exc_file = content_load(
+            content,
+            format,
+            Path::new(""),
+            &mut Vec::new(),
+            &self.messages,
+        )?;
+        self.exc_install(label_index(exc_file, &self.messages)?)
+    }
+
+    /// Install the command set built in code and start the file run like an imported file,
+    /// the included files are relative to the current directory.
+    pub fn load(&mut self, exc_file: ExecuteFile) -> Result<(), DataError> {
+        // clear the saved command set.
+        Console::exc_clear(self);
+
+        let exc_file = includes_load(exc_file, Path::new(""), &mut Vec::new(), &self.messages)?;
+        self.exc_install(label_index(exc_file, &self.messages)?)
+    }
+
+    /// Import every `.toml` file of the directory in lexical order as one file run,
+    /// which executes the merged instruction sets once. A file failing to load is reported as
+    /// `DataError::File` with its path and its error.
+    pub fn import_dir(&mut self, dir: &Path) -> Result<(), DataError> {
+        // clear the saved command set.
+        Console::exc_clear(self);
+
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_file() && path.extension().is_some_and(|v| v == "toml") {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        let mut exc_file = ExecuteFile::default();
+        for path in paths {
+            let loaded = file_load(&path, &mut Vec::new(), &self.messages).map_err(|err_info| {
+                DataError::File {
+                    path: path.clone(),
+                    source: Box::new(err_info),
+                }
+            })?;
+            exc_file.exc_ins_assets.extend(loaded.exc_ins_assets);
+        }
+        self.exc_install(label_index(exc_file, &self.messages)?)
+    }
+
+    /// Install the loaded command set and start the file run.
+    fn exc_install(&mut self, exc_file: ExecuteFile) -> Result<(), DataError> {
+        self.exc_arm(exc_file)?;
+
+        // the import is executed as a terminal instruction, then the file acquisition begins.
+        self.check.read_valid = true;
+        self.check.file_valid = true;
+        self.status.current = ConsoleStatus::InsExecFromTerminal;
+        self.refresh()?;
+        Ok(())
+    }
+
+    /// Install the loaded command set with new run statistics, and point the file cursor at its
+    /// first command. The command set is cleared when it is not executable.
+    fn exc_arm(&mut self, exc_file: ExecuteFile) -> Result<(), DataError> {
+        self.auto_exc = exc_file;
+        self.order_shuffle();
+
+        // pre-population.
+        if self.auto_exc.stream.is_none()
+            && !(0..self.auto_exc.exc_ins_assets.len()).any(|v| self.ins_selected(v))
+        {
+            Console::exc_clear(self);
+            return Err(DataError::Redaction(
+                "没有可执行的主指令集，检查标签过滤。".to_string(),
+            ));
+        }
+        self.file_poll()?;
+        if let Err(err_info) = self.verify_integrity() {
+            Console::exc_clear(self);
+            return Err(err_info);
+        }
+        self.last_result = true;
+        self.summary = RunSummary::default();
+        self.command_counts.1 = 0;
+        self.served = None;
+        self.retry = None;
+        self.deadline = None;
+        self.expect_results.clear();
+        self.expect_pending = false;
+        self.batch_error = None;
+        self.step_wait = false;
+        self.run_log = None;
+        self.check.import_valid = true;
+        self.progress_reset();
+        self.run_start = Some(Instant::now());
+        self.debug_log(&format!(
+            " * 导入主指令集 {} 条，执行 {} 次",
+            self.auto_exc.exc_ins_assets.len(),
+            self.auto_exc.cycle_times.unwrap_or(1)
+        ));
+        Ok(())
+    }
+
+    /// Check the loaded command set is well-formed: instruction sets are loaded, the file cursor,
+    /// the execution order and the labels point at them, and the cycle times are within the
+    /// bound. Nothing is changed, re-import the file on error.
+    pub fn verify_integrity(&self) -> Result<(), DataError> {
+        let auto_exc = &self.auto_exc;
+        let len = auto_exc.exc_ins_assets.len();
+        let corrupted = |message: &str| Err(DataError::Corrupted(message.to_string()));
+        if len == 0 && auto_exc.stream.is_none() {
+            return corrupted(self.messages.ins_polluted);
+        }
+
+        match (auto_exc.next_exc_ins, auto_exc.next_exc_cmd) {
+            (None, Some(_)) => return corrupted(self.messages.ins_lost),
+            (Some(ins_index), _) if ins_index >= len => {
+                return corrupted(self.messages.ins_read_failed)
+            }
+            (Some(ins_index), Some(cmd_index)) => {
+                let Some(sub_cmd_assets) = &auto_exc.exc_ins_assets[ins_index].sub_cmd_assets
+                else {
+                    return corrupted(self.messages.cmd_lost);
+                };
+                if cmd_index >= sub_cmd_assets.len() {
+                    return corrupted(&Messages::fill(
+                        self.messages.cmd_out_of_range,
+                        &[&(ins_index + 1), &(cmd_index + 1)],
+                    ));
+                }
+            }
+            _ => {}
+        }
+
+        let mut order = auto_exc.order.clone();
+        order.sort_unstable();
+        if !order.is_empty() && !order.iter().copied().eq(0..len) {
+            return corrupted(self.messages.order_inconsistent);
+        }
+        if let Some((label, _)) = auto_exc.labels.iter().find(|(_, &v)| v >= len) {
+            return corrupted(&Messages::fill(self.messages.label_dangling, &[label]));
+        }
+
+        let cycle_times = std::iter::once(auto_exc.cycle_times)
+            .chain(auto_exc.exc_ins_assets.iter().map(|v| v.cycle_times));
+        if let Some(cycle_times) = cycle_times.flatten().find(|&v| v > CYCLE_TIMES_MAX) {
+            return corrupted(&Messages::fill(
+                self.messages.cycle_times_exceeded,
+                &[&cycle_times, &CYCLE_TIMES_MAX],
+            ));
+        }
+        Ok(())
+    }
+
+    /// Read the terminal commands from the input instead of the stdin.
+    pub fn set_input(&mut self, input: impl ConsoleInput + 'static) {
+        self.input = Box::new(input);
+    }
+
+    /// Read the imported command list files line by line during the run instead of loading
+    /// them whole, which bounds the memory of huge files. Only the current instruction set
+    /// is kept, so the labels can not be jumped to.
+    pub fn set_list_streaming(&mut self, streaming: bool) {
+        self.list_streaming = streaming;
+    }
+
+    /// Dry-run the file of the path: parse it and check every instruction and command,
+    /// without touching the imported command set or the console status.
+    /// Returns the total number of instructions and commands.
+    pub fn validate_file(&mut self, path: &str) -> Result<usize, DataError> {
+        let exc_file = file_load(Path::new(path), &mut Vec::new(), &self.messages)?;
+
+        let mut total = 0;
+        for (ins_index, exc_assets) in exc_file.exc_ins_assets.iter().enumerate() {
+            let sub_cmds = exc_assets.sub_cmd_assets.iter().flatten();
+            for cmd in std::iter::once(&exc_assets.exc_ins).chain(sub_cmds.map(|v| &v.sub_cmd)) {
+                let input = cmd.expand(&self.vars).map(|v| self.input_parser(v));
+                if let Err(err_info) = input.and_then(|v| self.input_check(&v).map(|_| v)) {
+                    return Err(DataError::Redaction(format!(
+                        "第 {} 条主指令集的命令 {} 无效：{}",
+                        ins_index + 1,
+                        cmd,
+                        err_info
+                    )));
+                }
+                total += 1;
+            }
+        }
+        Ok(total)
+    }
+
+    pub fn file_import_no_err(&mut self) {
         match self.file_import() {
             Ok(_) => {}
-            Err(err_info) => {
-                // if the log mutex acquisition fails, it will panic automatically.
-                match self.log.lock().map_err(|_| {
-                    DataError::Redaction(
-                        "log information prints mutex acquisition failure.".to_string(),
-                    )
-                }) {
-                    Ok(log) => log.err_log(&err_info),
-                    Err(_err_info) => {
-                        panic!("{}", _err_info);
-                    }
+            Err(err_info) => self.err_log(&err_info),
+        }
+    }
+
+    fn file_poll(&mut self) -> Result<(), DataError> {
+        let cursor = (self.auto_exc.next_exc_ins, self.auto_exc.next_exc_cmd);
+        let polled = self.cursor_poll();
+        self.debug_log(&format!(
+            " * 文件游标 {:?} -> {:?}",
+            cursor,
+            (self.auto_exc.next_exc_ins, self.auto_exc.next_exc_cmd)
+        ));
+        polled
+    }
+
+    /// Move the file cursor (instruction set, command) to the next file command.
+    fn cursor_poll(&mut self) -> Result<(), DataError> {
+        match (self.auto_exc.next_exc_ins, self.auto_exc.next_exc_cmd) {
+            (None, None) => {
+                if self.auto_exc.exc_ins_assets.is_empty() && self.auto_exc.stream.is_none() {
+                    Console::exc_clear(self);
+                    return Err(DataError::Corrupted(self.messages.ins_polluted.to_string()));
+                }
+                self.ins_advance(0)?;
+                // the streamed command list has no instruction.
+                if self.auto_exc.next_exc_ins.is_none() {
+                    Console::exc_clear(self);
+                    return Err(DataError::Corrupted(self.messages.ins_polluted.to_string()));
+                }
+            }
+            (None, Some(_)) => {
+                Console::exc_clear(self);
+                return Err(DataError::Corrupted(self.messages.ins_lost.to_string()));
+            }
+            (Some(ins_index), None) => {
+                // Go to the instruction set pointed to by the index.
+                let Some(exc_assets) = self.auto_exc.exc_ins_assets.get(ins_index) else {
+                    Console::exc_clear(self);
+                    return Err(DataError::Corrupted(
+                        self.messages.ins_read_failed.to_string(),
+                    ));
+                };
+                // Get the first command in the instruction set
+                if exc_assets
+                    .sub_cmd_assets
+                    .as_ref()
+                    .is_some_and(|v| !v.is_empty())
+                {
+                    self.auto_exc.next_exc_cmd = Some(0);
+                } else {
+                    // No command in instruction set.
+                    self.ins_finish(ins_index)?;
+                }
+            }
+            (Some(ins_index), Some(cmd_index)) => {
+                // Go to the instruction set pointed to by the index.
+                let Some(exc_assets) = self.auto_exc.exc_ins_assets.get(ins_index) else {
+                    Console::exc_clear(self);
+                    return Err(DataError::Corrupted(self.messages.ins_lost.to_string()));
+                };
+                let Some(sub_cmd_assets) = &exc_assets.sub_cmd_assets else {
+                    Console::exc_clear(self);
+                    return Err(DataError::Corrupted(self.messages.cmd_lost.to_string()));
+                };
+                // Go to the command set pointed to by the index.
+                if cmd_index + 1 < sub_cmd_assets.len() {
+                    // Get the next command in the instruction set
+                    self.auto_exc.next_exc_cmd = Some(cmd_index + 1);
+                } else {
+                    // All commands in instruction set have been polled.
+                    self.ins_finish(ins_index)?;
                 }
             }
         }
+
+        // follow the jumps to the labeled instruction sets.
+        while let Some((ins_index, label)) = self.goto_pending() {
+            self.ins_goto(ins_index, &label)?;
+        }
+
+        match self.auto_exc.pending() {
+            Some(_) => Ok(()),
+            None => Err(DataError::Unknown),
+        }
     }
 
-    fn file_poll(&mut self) -> Result<String, DataError> {
-        match self.auto_exc.next_exc_ins {
-            None => match self.auto_exc.next_exc_cmd {
-                None => {
-                    if let Some(exc_assets) = self.auto_exc.exc_ins_assets.get(0) {
-                        self.auto_exc.next_exc_ins = Some((0, exc_assets.exc_ins.clone()));
-                        // set delay time.
-                        if let Some(delay) = exc_assets.delay {
-                            self.delay = (delay, self.delay.0);
-                        } else {
-                            self.delay = (0, self.delay.0);
-                        }
-                        // set expect finish time.
-                        if let Some(t_expect_finish) = exc_assets.t_expect_finish {
-                            self.t_expect_finish = (
-                                self.t_expect_finish.0,
-                                t_expect_finish,
-                                self.t_expect_finish.1,
-                            );
-                        } else {
-                            self.t_expect_finish =
-                                (self.t_expect_finish.0, 0, self.t_expect_finish.1);
-                        }
-                    } else {
+    /// Point the file cursor at the instruction set of the index.
+    fn ins_enter(&mut self, ins_index: usize) {
+        self.auto_exc.next_exc_ins = Some(ins_index);
+        self.auto_exc.next_exc_cmd = None;
+        self.auto_exc.ins_cycle_times = self.auto_exc.exc_ins_assets[ins_index].cycle_times;
+        self.ins_timing(ins_index);
+    }
+
+    /// Point the file cursor at the first executable instruction set starting from the index,
+    /// the end of the file enters the next cycle or finishes the automatic execution.
+    fn ins_advance(&mut self, mut ins_pos: usize) -> Result<(), DataError> {
+        loop {
+            if ins_pos >= self.auto_exc.exc_ins_assets.len() {
+                // the streamed command list only holds the current instruction set.
+                match self.auto_exc.stream_next() {
+                    Ok(true) => {
+                        ins_pos = 0;
+                        continue;
+                    }
+                    Ok(false) => {}
+                    Err(err_info) => {
                         Console::exc_clear(self);
-                        return Err(DataError::Redaction(format!(
-                            "获取第一条主指令集失败，文件导入的指令集内容被污染，请重新导入文件。"
-                        )));
+                        return Err(err_info);
                     }
                 }
-                Some((_cmd_index, _)) => {
+
+                // End of file instruction set traversal.
+                self.auto_exc.next_exc_ins = None;
+                self.auto_exc.next_exc_cmd = None;
+
+                // cycle judgment
+                if !self.cycle_next() {
                     Console::exc_clear(self);
-                    return Err(DataError::Redaction(format!(
-                        "子命令的主指令意外丢失，请重新导入文件开始测试。"
-                    )));
+                    return Ok(());
                 }
-            },
-            Some((ins_index, _)) => {
-                match self.auto_exc.next_exc_cmd {
-                    None => {
-                        // Go to the instruction set pointed to by the index.
-                        if let Some(exc_assets) = self.auto_exc.exc_ins_assets.get(ins_index) {
-                            if let Some(sub_cmd_assets) = &exc_assets.sub_cmd_assets {
-                                if let Some(cmd) = sub_cmd_assets.get(0) {
-                                    // Get the first command in the instruction set
-                                    self.auto_exc.next_exc_cmd = Some((0, cmd.sub_cmd.clone()));
-                                }
-                            } else {
-                                // No command in instruction set.
-                                // Get the next instruction set instruction.
-                                if let Some(exc_assets) =
-                                    self.auto_exc.exc_ins_assets.get(ins_index + 1)
-                                {
-                                    self.auto_exc.next_exc_ins =
-                                        Some((ins_index + 1, exc_assets.exc_ins.clone()));
-                                    // set delay time.
-                                    if let Some(delay) = exc_assets.delay {
-                                        self.delay = (delay, self.delay.0);
-                                    } else {
-                                        self.delay = (0, self.delay.0);
-                                    }
-                                    // set expect finish time.
-                                    if let Some(t_expect_finish) = exc_assets.t_expect_finish {
-                                        self.t_expect_finish = (
-                                            self.t_expect_finish.0,
-                                            t_expect_finish,
-                                            self.t_expect_finish.1,
-                                        );
-                                    } else {
-                                        self.t_expect_finish =
-                                            (self.t_expect_finish.0, 0, self.t_expect_finish.1);
-                                    }
-                                } else {
-                                    // End of file instruction set traversal.
-                                    self.auto_exc.next_exc_ins = None;
-
-                                    // cycle judgment
-                                    if match self.auto_exc.cycle_times {
-                                        Some(cycle_times) => {
-                                            self.auto_exc.cycle_times = Some(cycle_times - 1);
-                                            cycle_times - 1
-                                        }
-                                        None => 0,
-                                    } != 0
-                                    {
-                                        if let Some(exc_assets) =
-                                            self.auto_exc.exc_ins_assets.get(0)
-                                        {
-                                            self.auto_exc.next_exc_ins =
-                                                Some((0, exc_assets.exc_ins.clone()));
-                                            // set delay time.
-                                            if let Some(delay) = exc_assets.delay {
-                                                self.delay = (delay, self.delay.0);
-                                            } else {
-                                                self.delay = (0, self.delay.0);
-                                            }
-                                            // set expect finish time.
-                                            if let Some(t_expect_finish) =
-                                                exc_assets.t_expect_finish
-                                            {
-                                                self.t_expect_finish = (
-                                                    self.t_expect_finish.0,
-                                                    t_expect_finish,
-                                                    self.t_expect_finish.1,
-                                                );
-                                            } else {
-                                                self.t_expect_finish = (
-                                                    self.t_expect_finish.0,
-                                                    0,
-                                                    self.t_expect_finish.1,
-                                                );
-                                            }
-                                        } else {
-                                            Console::exc_clear(self);
-                                            return Err(DataError::Redaction(format!(
-                                                "获取第一条主指令集失败，文件导入的指令集内容被污染，请重新导入文件。"
-                                            )));
-                                        }
-                                    } else {
-                                        Console::exc_clear(self);
-                                    }
-                                }
-                            }
-                        } else {
-                            Console::exc_clear(self);
-                            return Err(DataError::Redaction(format!(
-                                "读取指定主指令集失败，请重新导入文件开始测试。"
-                            )));
-                        }
-                    }
-                    Some((cmd_index, _)) => {
-                        // Go to the instruction set pointed to by the index.
-                        if let Some(exc_assets) = self.auto_exc.exc_ins_assets.get(ins_index) {
-                            if let Some(sub_cmd_assets) = &exc_assets.sub_cmd_assets {
-                                // Go to the command set pointed to by the index.
-                                if let Some(cmd) = sub_cmd_assets.get(cmd_index + 1) {
-                                    // Get the next command in the instruction set
-                                    self.auto_exc.next_exc_cmd =
-                                        Some((cmd_index + 1, cmd.sub_cmd.clone()));
-                                } else {
-                                    // No command in instruction set.
-                                    // Get the next instruction set instruction.
-                                    if let Some(exc_assets) =
-                                        self.auto_exc.exc_ins_assets.get(ins_index + 1)
-                                    {
-                                        self.auto_exc.next_exc_cmd = None;
-                                        self.auto_exc.next_exc_ins =
-                                            Some((ins_index + 1, exc_assets.exc_ins.clone()));
-                                        // set delay time.
-                                        if let Some(delay) = exc_assets.delay {
-                                            self.delay = (delay, self.delay.0);
-                                        } else {
-                                            self.delay = (0, self.delay.0);
-                                        }
-                                        // set expect finish time.
-                                        if let Some(t_expect_finish) = exc_assets.t_expect_finish {
-                                            self.t_expect_finish = (
-                                                self.t_expect_finish.0,
-                                                t_expect_finish,
-                                                self.t_expect_finish.1,
-                                            );
-                                        } else {
-                                            self.t_expect_finish =
-                                                (self.t_expect_finish.0, 0, self.t_expect_finish.1);
-                                        }
-                                    } else {
-                                        // End of file instruction set traversal.
-                                        self.auto_exc.next_exc_ins = None;
-                                        self.auto_exc.next_exc_cmd = None;
-
-                                        // cycle judgment
-                                        if match self.auto_exc.cycle_times {
-                                            Some(cycle_times) => {
-                                                self.auto_exc.cycle_times = Some(cycle_times - 1);
-                                                cycle_times - 1
-                                            }
-                                            None => 0,
-                                        } != 0
-                                        {
-                                            if let Some(exc_assets) =
-                                                self.auto_exc.exc_ins_assets.get(0)
-                                            {
-                                                self.auto_exc.next_exc_ins =
-                                                    Some((0, exc_assets.exc_ins.clone()));
-                                                // set delay time.
-                                                if let Some(delay) = exc_assets.delay {
-                                                    self.delay = (delay, self.delay.0);
-                                                } else {
-                                                    self.delay = (0, self.delay.0);
-                                                }
-                                                // set expect finish time.
-                                                if let Some(t_expect_finish) =
-                                                    exc_assets.t_expect_finish
-                                                {
-                                                    self.t_expect_finish = (
-                                                        self.t_expect_finish.0,
-                                                        t_expect_finish,
-                                                        self.t_expect_finish.1,
-                                                    );
-                                                } else {
-                                                    self.t_expect_finish = (
-                                                        self.t_expect_finish.0,
-                                                        0,
-                                                        self.t_expect_finish.1,
-                                                    );
-                                                }
-                                            } else {
-                                                Console::exc_clear(self);
-                                                return Err(DataError::Redaction(format!(
-                                                    "获取第一条主指令集失败，文件导入的指令集内容被污染，请重新导入文件。"
-                                                )));
-                                            }
-                                        } else {
-                                            Console::exc_clear(self);
-                                        }
-                                    }
-                                }
-                            } else {
-                                Console::exc_clear(self);
-                                return Err(DataError::Redaction(format!(
-                                    "指定主指令集的子命令集意外丢失，请重新导入文件开始测试。"
-                                )));
-                            }
-                        } else {
-                            Console::exc_clear(self);
-                            return Err(DataError::Redaction(format!(
-                                "子命令的主指令意外丢失，请重新导入文件开始测试。"
-                            )));
-                        }
-                    }
+                if self.auto_exc.exc_ins_assets.is_empty() {
+                    Console::exc_clear(self);
+                    return Err(DataError::Corrupted(self.messages.ins_polluted.to_string()));
                 }
+                self.order_shuffle();
+                ins_pos = 0;
             }
+
+            let ins_index = self.ins_at(ins_pos);
+            if !self.ins_selected(ins_index) {
+                ins_pos += 1;
+                continue;
+            }
+            // the grouping instruction set only prints its header.
+            if self.ins_is_group(ins_index) {
+                let header = format!(
+                    " * 指令组 {}",
+                    self.auto_exc.exc_ins_assets[ins_index].exc_ins
+                );
+                self.file_log(&header);
+                ins_pos += 1;
+                continue;
+            }
+
+            self.ins_enter(ins_index);
+            return Ok(());
+        }
+    }
+
+    /// Instruction set index at the position of the execution order.
+    fn ins_at(&self, ins_pos: usize) -> usize {
+        self.auto_exc.order.get(ins_pos).copied().unwrap_or(ins_pos)
+    }
+
+    /// Position of the instruction set of the index in the execution order.
+    fn ins_pos(&self, ins_index: usize) -> usize {
+        self.auto_exc
+            .order
+            .iter()
+            .position(|&v| v == ins_index)
+            .unwrap_or(ins_index)
+    }
+
+    /// Draw a new random execution order when the imported file requests shuffling.
+    fn order_shuffle(&mut self) {
+        if !self.auto_exc.shuffle.unwrap_or(false) {
+            return;
         }
+        let mut order: Vec<usize> = (0..self.auto_exc.exc_ins_assets.len()).collect();
+        order.shuffle(&mut self.rng);
+        self.auto_exc.order = order;
+    }
 
-        if let Some((_, cmd)) = self.auto_exc.next_exc_cmd.clone() {
-            match cmd {
-                GenericCmd::Number(cmd) => Ok(cmd.to_string()),
-                GenericCmd::Character(cmd) => Ok(cmd),
+    /// Seed the random source of the shuffled instruction order and the delay jitter,
+    /// for reproducible runs.
+    pub fn set_shuffle_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    /// Consume one automatic execution cycle, false if no cycle remains.
+    fn cycle_next(&mut self) -> bool {
+        match self.auto_exc.cycle_times {
+            Some(cycle_times) => {
+                self.auto_exc.cycle_times = Some(cycle_times.saturating_sub(1));
+                cycle_times.saturating_sub(1) != 0
             }
-        } else {
-            if let Some((_, cmd)) = self.auto_exc.next_exc_ins.clone() {
-                match cmd {
-                    GenericCmd::Number(cmd) => Ok(cmd.to_string()),
-                    GenericCmd::Character(cmd) => Ok(cmd),
+            None => false,
+        }
+    }
+
+    /// The pending jump command, with the index of the instruction set it belongs to.
+    fn goto_pending(&self) -> Option<(usize, String)> {
+        let ins_index = self.auto_exc.next_exc_ins?;
+        self.auto_exc
+            .pending()?
+            .goto_label()
+            .map(|label| (ins_index, label.to_string()))
+    }
+
+    /// Jump from the instruction set of the index to the labeled instruction set,
+    /// jumping backward starts a new automatic execution cycle.
+    fn ins_goto(&mut self, ins_index: usize, label: &str) -> Result<(), DataError> {
+        let Some(&target) = self.auto_exc.labels.get(label) else {
+            Console::exc_clear(self);
+            return Err(DataError::Corrupted(Messages::fill(
+                self.messages.goto_undefined,
+                &[&label],
+            )));
+        };
+        if self.ins_pos(target) <= self.ins_pos(ins_index) {
+            if !self.cycle_next() {
+                Console::exc_clear(self);
+                return Ok(());
+            }
+            self.order_shuffle();
+        }
+        self.ins_advance(self.ins_pos(target))
+    }
+
+    /// Skip the pending instruction sets which require the previous command to succeed.
+    fn ins_skip_failed(&mut self) -> Result<(), DataError> {
+        while !self.last_result {
+            let Some(ins_index) = self.auto_exc.next_exc_ins else {
+                break;
+            };
+            let exc_assets = &self.auto_exc.exc_ins_assets[ins_index];
+            if self.auto_exc.next_exc_cmd.is_some()
+                || !exc_assets.skip_if_prev_failed.unwrap_or(false)
+            {
+                break;
+            }
+
+            let notice = format!(" * 上一条命令执行失败，跳过指令 {}", exc_assets.exc_ins);
+            self.file_log(&notice);
+            self.ins_advance(self.ins_pos(ins_index) + 1)?;
+            self.progress_reset();
+        }
+        Ok(())
+    }
+
+    /// Whether the instruction set of the index is a pure grouping node.
+    fn ins_is_group(&self, ins_index: usize) -> bool {
+        self.group_only
+            && self.auto_exc.exc_ins_assets[ins_index]
+                .sub_cmd_assets
+                .as_ref()
+                .is_none_or(|v| v.is_empty())
+    }
+
+    /// Load the delay and expect finish time of the instruction set of the index.
+    fn ins_timing(&mut self, ins_index: usize) {
+        let (mut delay, jitter, t_expect_finish) = match self.auto_exc.exc_ins_assets.get(ins_index)
+        {
+            Some(exc_assets) => (
+                exc_assets.delay.unwrap_or(0),
+                exc_assets.delay_jitter.unwrap_or(0),
+                exc_assets.t_expect_finish.unwrap_or(0),
+            ),
+            None => (0, 0, 0),
+        };
+        let jitter = self.delay_jitter.map_or(jitter, |v| v.as_millis() as usize);
+        if delay != 0 && jitter != 0 {
+            delay = (delay + self.rng.random_range(0..=2 * jitter)).saturating_sub(jitter);
+        }
+        // set delay time.
+        self.delay = (delay, self.delay.0);
+        // set expect finish time.
+        self.t_expect_finish = (
+            self.t_expect_finish.0,
+            t_expect_finish,
+            self.t_expect_finish.1,
+        );
+    }
+
+    /// The instruction set of the index has been polled, repeat it or get the next instruction set.
+    fn ins_finish(&mut self, ins_index: usize) -> Result<(), DataError> {
+        // instruction set cycle judgment.
+        if let Some(cycle_times) = self.auto_exc.ins_cycle_times {
+            if cycle_times > 1 {
+                self.auto_exc.ins_cycle_times = Some(cycle_times - 1);
+                match self.auto_exc.cmd_at(ins_index, 0) {
+                    // loop the commands of the instruction set.
+                    Some(_) => self.auto_exc.next_exc_cmd = Some(0),
+                    // no command in instruction set, repeat the instruction itself.
+                    None => self.ins_timing(ins_index),
                 }
-            } else {
-                Err(DataError::Unknown)
+                return Ok(());
             }
         }
+
+        // Get the next instruction set instruction.
+        self.ins_advance(self.ins_pos(ins_index) + 1)
     }
 
     pub fn read(&mut self, prompt: &str) -> Result<String, DataError> {
-        // print prompt.
-        match self.log.lock().map_err(|_| {
-            DataError::Redaction("log information prints mutex acquisition failure.".to_string())
-        }) {
-            Ok(log) => {
-                if prompt == "" {
-                    log.prompt_log(&format!(
-                        "{}{}",
-                        self.interact.main_prompt, self.interact.sub_prompt
-                    ))
-                } else {
-                    log.prompt_log(&format!(
-                        "{}{}\r\n{}",
-                        self.interact.main_prompt, self.interact.sub_prompt, prompt
-                    ))
-                }
+        let start = Instant::now();
+        self.timing_poll(start);
+        self.read_input = None;
+        let cmd = self.read_command(prompt);
+        if let Ok(input) = &cmd {
+            self.rate_wait();
+            self.timing = Some((input.clone(), start));
+        }
+        let input = match &cmd {
+            Ok(input) => Some(input.clone()),
+            Err(_) => self.read_input.take(),
+        };
+        if let (Some(postprocessor), Some(input)) = (self.hooks.postprocessor.as_mut(), input) {
+            postprocessor(&input);
+        }
+        cmd
+    }
+
+    /// Hold the command until the minimum interval of the rate limit has passed since the
+    /// previous command.
+    fn rate_wait(&mut self) {
+        if let (Some(interval), Some(last)) = (self.rate_interval, self.rate_last) {
+            if let Some(wait) = interval.checked_sub(last.elapsed()) {
+                thread::sleep(wait);
             }
-            Err(_err_info) => {
-                panic!("{}", _err_info);
+        }
+        self.rate_last = Some(Instant::now());
+    }
+
+    /// Vary every delay of the file by a random `± jitter`, instead of the `delay_jitter` of its
+    /// instruction sets. None restores the file ones.
+    ///
+    /// The delays count as time already taken by the rate limit, a command is held only for
+    /// the rest of its interval.
+    pub fn set_delay_jitter(&mut self, jitter: Option<Duration>) {
+        self.delay_jitter = jitter;
+    }
+
+    /// Limit the commands read from the terminal and the file to the commands per second,
+    /// the time the previous command already took is deducted. None disables it.
+    pub fn set_rate_limit(&mut self, per_second: Option<f64>) {
+        self.rate_interval = per_second
+            .filter(|v| *v > 0.0)
+            .map(|v| Duration::from_secs_f64(1.0 / v));
+    }
+
+    fn read_command(&mut self, prompt: &str) -> Result<String, DataError> {
+        self.interrupt_poll()?;
+        // only the file commands wait for the resume, the terminal is still read.
+        if let (true, ConsoleStatus::InsAcqFromFile | ConsoleStatus::InsExecFromFile) =
+            (self.paused, &self.status.current)
+        {
+            return Err(DataError::Paused);
+        }
+        self.breakpoint_poll()?;
+        self.deadline_poll()?;
+        self.file_watch_poll();
+        self.expect_summary_poll();
+        if let ConsoleStatus::InsAcqFromFile | ConsoleStatus::InsExecFromFile = self.status.current
+        {
+            self.run_log_open();
+        }
+
+        // print prompt.
+        if let Some(prompt_fn) = self.hooks.prompt.as_ref() {
+            self.interact.main_prompt = prompt_fn();
+        }
+        self.prompt_show(prompt);
+
+        // re-issue the file command reported failed.
+        if let Some(cmd) = self.retry_next() {
+            return Ok(cmd);
+        }
+
+        // skipping the last instruction sets ends the file run, the terminal is read instead.
+        if let ConsoleStatus::InsAcqFromFile = self.status.current {
+            self.ins_skip_failed()?;
+            if self.auto_exc.next_exc_ins.is_none() {
+                self.run_finish();
+                self.refresh()?;
+                self.prompt_show(prompt);
             }
         }
 
@@ -651,7 +1929,11 @@ where
                 self.terminal_read(prompt)
             }
             ConsoleStatus::InsAcqFromFile | ConsoleStatus::InsExecFromFile => {
-                self.file_read(prompt)
+                self.step_gate();
+                let cmd = self.file_read(prompt);
+                self.step_wait = self.step_mode;
+                self.run_track(cmd.is_ok());
+                cmd
             }
             ConsoleStatus::Invalid => {
                 self.refresh()?;
@@ -662,32 +1944,267 @@ where
             }
         };
 
-        match cmd {
-            Ok(cmd) => {
-                self.refresh()?;
-                return Ok(cmd);
+        self.refresh()?;
+        cmd
+    }
+
+    /// Register the terminal command listed by the built-in `help`, with its description.
+    /// Once a command is registered, `help` lists them and `help <cmd>` shows the one, the
+    /// `help` commands are answered by the console and never returned by `read`.
+    pub fn register_command(&mut self, name: &str, desc: Option<&str>) {
+        self.commands
+            .insert(name.to_string(), desc.map(|v| v.to_string()));
+    }
+
+    /// Answer the `help` command through the prompt log, even when quiet, false for any other command or
+    /// without registered commands.
+    fn help(&self, input: &str) -> bool {
+        let mut words = input.split_whitespace();
+        if self.commands.is_empty() || words.next() != Some("help") {
+            return false;
+        }
+        let line = |name: &str, desc: &Option<String>| match desc {
+            Some(desc) => format!("  {}  {}", name, desc),
+            None => format!("  {}", name),
+        };
+        match words.next() {
+            Some(name) => match self.commands.get(name) {
+                Some(desc) => self.reply_log(&line(name, desc)),
+                None => self.reply_log(&format!("命令 {} 未注册。", name)),
+            },
+            None => {
+                self.reply_log("可用命令：");
+                for (name, desc) in &self.commands {
+                    self.reply_log(&line(name, desc));
+                }
             }
-            Err(err_info) => {
-                self.refresh()?;
-                return Err(err_info);
+        }
+        true
+    }
+
+    /// Forget the lines recalled by the terminal input, e.g. between the sessions of a shared
+    /// terminal. Nothing persisted is touched.
+    pub fn clear_history(&mut self) {
+        self.input.clear_history();
+    }
+
+    /// Record the terminal lines read from now on in the input history, enabled by default.
+    /// Disable it around the sensitive commands to keep them out of the history, the secrets
+    /// of `read_secret` are never recorded.
+    pub fn set_history_enabled(&mut self, enabled: bool) {
+        self.input.set_history_enabled(enabled);
+    }
+
+    /// Read a secret from the terminal without echo, e.g. a password. It is checked like a
+    /// command, but neither echoed after the prompt nor logged.
+    pub fn read_secret(&mut self, prompt: &str) -> Result<String, DataError> {
+        let input = self.prompt_line(prompt, true)?;
+        self.input_check(&input)?;
+        Ok(input)
+    }
+
+    /// Ask a yes/no question on the terminal until `y`/`yes`/`是` or `n`/`no`/`否` is answered,
+    /// in any case.
+    pub fn confirm(&mut self, prompt: &str) -> Result<bool, DataError> {
+        let prompt = format!("{} [y/n]", prompt);
+        loop {
+            match self
+                .prompt_line(&prompt, false)?
+                .trim()
+                .to_lowercase()
+                .as_str()
+            {
+                "y" | "yes" | "是" => return Ok(true),
+                "n" | "no" | "否" => return Ok(false),
+                _ => {}
+            }
+        }
+    }
+
+    /// List the options numbered from 1, even when quiet, and read the number of one until it
+    /// is valid, the zero-based index of the selected option is returned.
+    pub fn select(&mut self, prompt: &str, options: &[&str]) -> Result<usize, DataError> {
+        if options.is_empty() {
+            return Err(DataError::Redaction("没有可选择的选项。".to_string()));
+        }
+        for (index, option) in options.iter().enumerate() {
+            self.reply_log(&format!("  {}. {}", index + 1, option));
+        }
+        let prompt = format!("{} [1-{}]", prompt, options.len());
+        loop {
+            match self.prompt_line(&prompt, false)?.trim().parse::<usize>() {
+                Ok(number) if (1..=options.len()).contains(&number) => return Ok(number - 1),
+                _ => {}
+            }
+        }
+    }
+
+    /// Read a terminal value shown as `prompt [default]`, an empty input gives the default.
+    /// Both are checked like a command.
+    pub fn read_with_default(&mut self, prompt: &str, default: &str) -> Result<String, DataError> {
+        let mut input = self.prompt_line(&format!("{} [{}]", prompt, default), false)?;
+        if input.trim().is_empty() {
+            input = default.to_string();
+        }
+        self.input_check(&input)?;
+        Ok(input)
+    }
+
+    /// Read a terminal value within `min..=max`, e.g. a channel number. The invalid inputs
+    /// are logged as errors and the value is asked again. The bounds are not displayable,
+    /// state them in the prompt, e.g. `通道 [1 ~ 16]`.
+    pub fn read_in_range<V>(&mut self, prompt: &str, min: V, max: V) -> Result<V, DataError>
+    where
+        V: PartialOrd + FromStr,
+    {
+        loop {
+            let input = self.prompt_line(prompt, false)?;
+            let value = self.input_check(&input).and_then(|_| {
+                input
+                    .trim()
+                    .parse::<V>()
+                    .map_err(|_| DataError::InvalidHeader {
+                        expected: std::any::type_name::<V>().to_string(),
+                        found: input.clone(),
+                    })
+            });
+            match value {
+                Ok(value) if min <= value && value <= max => return Ok(value),
+                Ok(_) => {
+                    let err_info =
+                        DataError::Redaction(format!("输入 {} 超出范围。", input.trim()));
+                    self.err_log(&err_info);
+                }
+                Err(err_info) => self.err_log(&err_info),
+            }
+        }
+    }
+
+    /// Read a terminal keyword until it is one of the choices, the matched choice is returned.
+    /// The valid choices are logged after an invalid input.
+    pub fn read_choice(&mut self, prompt: &str, choices: &[&str]) -> Result<String, DataError> {
+        if choices.is_empty() {
+            return Err(DataError::Redaction("没有可选择的选项。".to_string()));
+        }
+        let prompt = format!("{} [{}]", prompt, choices.join("/"));
+        loop {
+            let input = self.prompt_line(&prompt, false)?;
+            let input = input.trim();
+            let choice = choices.iter().find(|v| match self.choice_ignore_case {
+                true => v.to_lowercase() == input.to_lowercase(),
+                false => **v == input,
+            });
+            if let Some(choice) = choice {
+                return Ok(choice.to_string());
+            }
+            let err_info = DataError::Redaction(format!(
+                "输入 {} 无效，可选：{}。",
+                input,
+                choices.join("、")
+            ));
+            self.err_log(&err_info);
+        }
+    }
+
+    /// Execute only the commands of the set, matched by the full command or its first word,
+    /// from the terminal and the file alike.
+    pub fn allow_commands(&mut self, set: HashSet<String>) {
+        self.allowed = Some(set);
+    }
+
+    /// Never execute the commands of the set, matched like `allow_commands` and taking
+    /// precedence over it.
+    pub fn deny_commands(&mut self, set: HashSet<String>) {
+        self.denied = set;
+    }
+
+    /// Accept more characters in the commands, the Unicode letters and digits are always accepted.
+    pub fn set_input_policy(&mut self, policy: InputPolicy) {
+        self.input_policy = policy;
+    }
+
+    /// Characters trimmed from the end of each input, `\r` and `\n` by default,
+    /// e.g. add `\0` for the inputs of a socket.
+    pub fn set_trim_chars(&mut self, trim_chars: &[char]) {
+        self.trim_chars = trim_chars.to_vec();
+    }
+
+    /// Normalize the CRLF inside the inputs to LF, e.g. for the multi-line file commands.
+    pub fn set_normalize_crlf(&mut self, normalize_crlf: bool) {
+        self.normalize_crlf = normalize_crlf;
+    }
+
+    /// Match the choices of `read_choice` in any case.
+    pub fn set_choice_ignore_case(&mut self, ignore_case: bool) {
+        self.choice_ignore_case = ignore_case;
+    }
+
+    /// Read terminal lines until the terminator line, e.g. `EOF`, and return them joined by `\n`.
+    /// The body is not restricted to the command characters, only to 64 KiB.
+    pub fn read_block(&mut self, prompt: &str, terminator: &str) -> Result<String, DataError> {
+        let mut line = self.prompt_line(prompt, false)?;
+        let mut body: Option<String> = None;
+        loop {
+            if line == terminator {
+                return Ok(body.unwrap_or_default());
+            }
+            // the line is checked before it is appended, so the body never exceeds the limit.
+            let len = body.as_ref().map_or(0, |v| v.len() + 1) + line.len();
+            if len > BLOCK_LEN_MAX {
+                return Err(DataError::Redaction(format!(
+                    "输入内容超过 {} 字节。",
+                    BLOCK_LEN_MAX
+                )));
+            }
+            match body.as_mut() {
+                Some(body) => {
+                    body.push('\n');
+                    body.push_str(&line);
+                }
+                None => body = Some(line),
+            }
+            let mut next = String::new();
+            if self.input.read_answer_line(&mut next)? == 0 {
+                return Err(DataError::Eof);
             }
+            line = self.input_parser(next);
+        }
+    }
+
+    /// Prompt and read a terminal line outside of the command flow, the state, the echoed
+    /// input, the command log and the input history are left untouched.
+    fn prompt_line(&mut self, prompt: &str, secret: bool) -> Result<String, DataError> {
+        self.prompt_log(&self.paint(color::CYAN, prompt).to_string());
+        self.event_send(ConsoleEvent::Prompt(prompt.to_string()));
+        let _ = io::stdout().flush();
+        let mut input = String::new();
+        let len = match secret {
+            true => self.input.read_secret_line(&mut input)?,
+            false => self.input.read_answer_line(&mut input)?,
+        };
+        if len == 0 {
+            return Err(DataError::Eof);
         }
+        Ok(self.input_parser(input))
+    }
+
+    /// Read a command and parse it into the requested type, e.g. a port number.
+    pub fn read_as<V: FromStr>(&mut self, prompt: &str) -> Result<V, DataError> {
+        let input = self.read(prompt)?;
+        input.parse::<V>().map_err(|_| DataError::InvalidHeader {
+            expected: std::any::type_name::<V>().to_string(),
+            found: input,
+        })
     }
 
     pub fn read_no_err(&mut self, prompt: &str) -> String {
         match self.read(prompt) {
             Ok(input) => input,
             Err(err_info) => {
-                // If the log mutex acquisition fails, it will panic automatically.
-                match self.log.lock().map_err(|_| {
-                    DataError::Redaction(
-                        "log information prints mutex acquisition failure.".to_string(),
-                    )
-                }) {
-                    Ok(log) => log.err_log(&err_info),
-                    Err(_err_info) => {
-                        panic!("{}", _err_info);
-                    }
+                self.err_log(&err_info);
+                // stop the corrupted file run and keep prompting from the terminal.
+                if err_info.is_fatal() {
+                    let _ = self.abort();
                 }
                 "".to_string()
             }
@@ -698,15 +2215,73 @@ where
         Ok("".to_string())
     }
 
-    /// Console state machine refresh
-    fn refresh(&mut self) -> Result<(), DataError> {
-        self.status.previous = self.status.current.clone();
-        self.status.current = match self.status.current {
-            ConsoleStatus::Invalid => ConsoleStatus::InsAcqFromTerminal,
+    /// Read up to `n` commands from the imported file as one batch,
+    /// fewer if the automatic execution ends before the batch is filled.
+    ///
+    /// A command failing after some were read ends the batch early with the ones read, the
+    /// error is returned by the next batch.
+    pub fn read_batch(&mut self, n: usize) -> Result<Vec<String>, DataError> {
+        if let Some(err_info) = self.batch_error.take() {
+            return Err(err_info);
+        }
+        let mut batch = Vec::with_capacity(n);
+        while batch.len() < n {
+            match self.status.current {
+                ConsoleStatus::InsAcqFromFile | ConsoleStatus::InsExecFromFile => {
+                    match self.read("") {
+                        Ok(cmd) => batch.push(cmd),
+                        Err(err_info) if batch.is_empty() => return Err(err_info),
+                        Err(err_info) => {
+                            self.batch_error = Some(err_info);
+                            break;
+                        }
+                    }
+                }
+                _ => break,
+            }
+        }
+        Ok(batch)
+    }
+
+    /// Read every command of the imported file until the file run ends, with the delays and
+    /// the cycle times of the file, e.g. for automated pipelines where the postprocessor
+    /// executes each command. The errors are logged, a fatal error aborts the file run and is
+    /// returned, the rejected commands are skipped unless `set_stop_on_error` is set.
+    ///
+    /// Returns immediately outside a file run, and with `DataError::Paused` when paused.
+    pub fn run_to_completion(&mut self) -> Result<(), DataError> {
+        while let ConsoleStatus::InsAcqFromFile | ConsoleStatus::InsExecFromFile =
+            self.status.current
+        {
+            if let Err(err_info) = self.read("") {
+                if let DataError::Paused = err_info {
+                    return Err(err_info);
+                }
+                self.err_log(&err_info);
+                if err_info.is_fatal() || self.stop_on_error {
+                    let _ = self.abort();
+                    return Err(err_info);
+                }
+            }
+            self.taildowm();
+        }
+        Ok(())
+    }
+
+    /// Stop `run_to_completion` at the first rejected command, aborting the file run.
+    pub fn set_stop_on_error(&mut self, stop_on_error: bool) {
+        self.stop_on_error = stop_on_error;
+    }
+
+    /// Console state machine refresh
+    fn refresh(&mut self) -> Result<(), DataError> {
+        self.status.previous = self.status.current.clone();
+        self.status.current = match self.status.current {
+            ConsoleStatus::Invalid => ConsoleStatus::InsAcqFromTerminal,
             ConsoleStatus::InsAcqFromFile => {
-                if let Some(_) = self.auto_exc.next_exc_cmd {
+                if self.auto_exc.next_exc_cmd.is_some() {
                     ConsoleStatus::InsExecFromFile
-                } else if let Some(_) = self.auto_exc.next_exc_ins {
+                } else if self.auto_exc.next_exc_ins.is_some() {
                     self.prompt_clear();
                     ConsoleStatus::InsAcqFromFile
                 } else {
@@ -721,9 +2296,9 @@ where
                 }
             }
             ConsoleStatus::InsExecFromFile => {
-                if let Some(_) = self.auto_exc.next_exc_cmd {
+                if self.auto_exc.next_exc_cmd.is_some() {
                     ConsoleStatus::InsExecFromFile
-                } else if let Some(_) = self.auto_exc.next_exc_ins {
+                } else if self.auto_exc.next_exc_ins.is_some() {
                     self.prompt_clear();
                     ConsoleStatus::InsAcqFromFile
                 } else {
@@ -733,7 +2308,7 @@ where
             ConsoleStatus::InsExecFromTerminal => match self.check.read_valid {
                 true => {
                     if self.check.file_valid {
-                        if let Some(_) = self.auto_exc.next_exc_ins {
+                        if self.auto_exc.next_exc_ins.is_some() {
                             self.prompt_clear();
                             ConsoleStatus::InsAcqFromFile
                         } else {
@@ -746,27 +2321,526 @@ where
                 false => ConsoleStatus::Invalid,
             },
         };
+        self.history_push();
+        self.debug_log(&format!(
+            " * 状态刷新 {:?} -> {:?}",
+            self.status.previous, self.status.current
+        ));
 
         if let ConsoleStatus::Invalid = self.status.current {
             self.prompt_clear();
             self.status.previous = self.status.current.clone();
             self.status.current = ConsoleStatus::InsAcqFromTerminal;
+            self.history_push();
+        }
+
+        if self.status.current != self.status.previous {
+            if !self.quiet {
+                let previous = format!("{:?}", self.status.previous);
+                let current = format!("{:?}", self.status.current);
+                self.log_lock().state_log(
+                    &self.paint(color::YELLOW, previous).to_string(),
+                    &self.paint(color::YELLOW, current).to_string(),
+                );
+            }
+            if let Some(transition) = self.hooks.transition.as_mut() {
+                transition(self.status.previous.clone(), self.status.current.clone());
+            }
+            for (_, listener) in self.hooks.listeners.iter_mut() {
+                listener(self.status.previous.clone(), self.status.current.clone());
+            }
+            self.event_send(ConsoleEvent::StateChange(
+                self.status.previous.clone(),
+                self.status.current.clone(),
+            ));
+        }
+
+        self.check_reset();
+        Ok(())
+    }
+
+    /// Pause the file run, `read` returns `DataError::Paused` instead of the file commands and
+    /// keeps the file run progress. The terminal commands are still read.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Resume the console, the file run continues from the command where it was paused.
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Pause the file run before the instruction set of the index executes, each time it is
+    /// reached. `read` returns `DataError::Paused` and `breakpoint_log` reports the index.
+    pub fn add_breakpoint(&mut self, index: usize) {
+        self.breakpoints.insert(index);
+    }
+
+    /// Remove the breakpoint of the instruction set of the index.
+    pub fn remove_breakpoint(&mut self, index: usize) {
+        self.breakpoints.remove(&index);
+    }
+
+    /// Instruction set index of the breakpoint the file run is paused at.
+    pub fn breakpoint_hit(&self) -> Option<usize> {
+        self.breakpoint_hit.filter(|_| self.paused)
+    }
+
+    /// Continue the file run paused at a breakpoint, the instruction set executes.
+    pub fn continue_run(&mut self) {
+        self.resume();
+    }
+
+    /// Pause the file run when the pending instruction set has a breakpoint, unless the run
+    /// just continued from it.
+    fn breakpoint_poll(&mut self) -> Result<(), DataError> {
+        let (ConsoleStatus::InsAcqFromFile, Some(ins_index)) =
+            (&self.status.current, self.auto_exc.next_exc_ins)
+        else {
+            return Ok(());
+        };
+        if !self.breakpoints.contains(&ins_index) || self.breakpoint_hit.take() == Some(ins_index) {
+            return Ok(());
         }
+        self.breakpoint_hit = Some(ins_index);
+        self.paused = true;
+        self.log_lock().breakpoint_log(ins_index);
+        Err(DataError::Paused)
+    }
 
-        //     if self.status.current != self.status.previous {
-        //         println!(
-        //             "
-        // + - - - - - - - - - + - - - - - - - - - - - - - - - - - - - - +
-        // |   控制台当前状态  |  {:?} -> {:?}
-        // + - - - - - - - - - + - - - - - - - - - - - - - - - - - - - - +",
-        //             self.status.previous, self.status.current
-        //         );
-        //     }
+    /// Abort the file run in progress from any state, the next `read` comes from the terminal.
+    pub fn abort(&mut self) -> Result<(), DataError> {
+        Console::exc_clear(self);
+        self.prompt_clear();
+        self.paused = false;
+        self.step_wait = false;
+        self.run_start = None;
+        self.run_log = None;
+        self.served = None;
+        self.retry = None;
+        self.deadline = None;
+        self.expect_pending = false;
+        self.current_ins = None;
+        self.current_cmd = None;
+
+        // the invalid state always refreshes to the terminal acquisition.
+        self.status.current = ConsoleStatus::Invalid;
+        self.refresh()
+    }
 
+    /// Reset the console to its freshly constructed state for a new session: the file run,
+    /// the prompt, the results and the history are cleared, then the console is set up again.
+    /// The configuration, the callbacks, the aliases and the variables are kept.
+    pub fn reset(&mut self) {
+        Console::exc_clear(self);
+        self.prompt_clear();
         self.check_reset();
+        self.current_ins = None;
+        self.current_cmd = None;
+
+        self.delay = (0, 0);
+        self.t_expect_finish = (0, 0, 0);
+        self.last_result = true;
+        self.summary = RunSummary::default();
+        self.run_start = None;
+        self.run_log = None;
+        self.history.clear();
+        self.paused = false;
+        self.breakpoint_hit = None;
+        self.step_wait = false;
+        self.served = None;
+        self.expect_results.clear();
+        self.expect_pending = false;
+        self.retry = None;
+        self.deadline = None;
+        self.rate_last = None;
+        self.timing = None;
+        self.read_input = None;
+        self.timings.clear();
+        self.command_counts = (0, 0);
+        self.interrupted.store(false, Ordering::SeqCst);
+        self.progress_total = 0;
+        self.watch = None;
+
+        self.status.current = ConsoleStatus::Invalid;
+        self.status.previous = ConsoleStatus::Invalid;
+        self.setup();
+    }
+
+    /// Expand the first word of the terminal commands matching the alias, e.g. `g` to `goto main`,
+    /// the rest of the command is kept. An alias expanding to itself, directly or through
+    /// other aliases, is rejected.
+    pub fn add_alias(&mut self, alias: &str, expansion: &str) -> Result<(), DataError> {
+        let mut aliases = self.aliases.clone();
+        aliases.insert(alias.to_string(), expansion.to_string());
+
+        let mut word = alias;
+        let mut expanded = vec![alias];
+        while let Some(next) = aliases.get(word).and_then(|v| v.split_whitespace().next()) {
+            if expanded.contains(&next) {
+                return Err(DataError::Redaction(format!("别名 {} 循环展开。", alias)));
+            }
+            expanded.push(next);
+            word = next;
+        }
+        self.aliases = aliases;
         Ok(())
     }
 
+    /// Set the value of `${name}` in the following file commands, e.g. a session token captured
+    /// from the reported output of a command. It shadows the environment variable of the name.
+    pub fn set_var(&mut self, name: &str, value: &str) {
+        self.vars.insert(name.to_string(), value.to_string());
+    }
+
+    pub fn get_var(&self, name: &str) -> Option<&str> {
+        self.vars.get(name).map(|v| v.as_str())
+    }
+
+    /// Strip the inline comments of the file commands from the delimiter, e.g. `#` for
+    /// `send 0xFF # set max`, before the input check. Quoted delimiters are kept, None disables it.
+    pub fn set_comment_delimiter(&mut self, delimiter: Option<char>) {
+        self.comment = delimiter;
+    }
+
+    /// Expand the aliases in the file commands too.
+    pub fn set_file_aliases(&mut self, file_aliases: bool) {
+        self.file_aliases = file_aliases;
+    }
+
+    /// Expand the aliases of the first word of the command until it is no alias.
+    fn alias_expand(&self, mut input: String) -> String {
+        // the aliases are acyclic, each is expanded at most once.
+        for _ in 0..self.aliases.len() {
+            let trimmed = input.trim_start();
+            let (word, rest) =
+                trimmed.split_at(trimmed.find(char::is_whitespace).unwrap_or(trimmed.len()));
+            let Some(expansion) = self.aliases.get(word) else {
+                break;
+            };
+            input = format!("{}{}", expansion, rest);
+        }
+        input
+    }
+
+    /// Handle Ctrl-C: the first interrupt aborts the file run on the next read and the terminal
+    /// input continues, a second interrupt within 2 s exits the process.
+    ///
+    /// The handler is process-wide and only sets a flag, it can be installed once.
+    #[cfg(feature = "interrupt")]
+    pub fn install_interrupt_handler(&self) -> Result<(), DataError> {
+        let interrupted = self.interrupted.clone();
+        let mut last: Option<Instant> = None;
+        ctrlc::set_handler(move || {
+            if last.is_some_and(|v| v.elapsed() < INTERRUPT_EXIT_WINDOW) {
+                std::process::exit(130);
+            }
+            last = Some(Instant::now());
+            interrupted.store(true, Ordering::SeqCst);
+        })
+        .map_err(|err_info| DataError::Redaction(format!("中断处理注册失败：{}", err_info)))
+    }
+
+    /// Abort the file run interrupted since the last read.
+    fn interrupt_poll(&mut self) -> Result<(), DataError> {
+        if !self.interrupted.swap(false, Ordering::SeqCst) {
+            return Ok(());
+        }
+        if let ConsoleStatus::InsAcqFromFile | ConsoleStatus::InsExecFromFile = self.status.current
+        {
+            self.file_log("测试流程已中断。");
+            self.abort()?;
+        }
+        Ok(())
+    }
+
+    /// Execute the file commands one at a time, each waits for `step` or the terminal enter.
+    pub fn set_step_mode(&mut self, step_mode: bool) {
+        self.step_mode = step_mode;
+        self.step_wait = false;
+    }
+
+    /// Release the next file command in step mode.
+    pub fn step(&mut self) {
+        self.step_wait = false;
+    }
+
+    /// Walk the commands the rest of the file run reads from the current file position, by the
+    /// same rules as `read`: the grouping instruction sets are passed, the jumps are followed
+    /// and the execution order is kept. The console state is left untouched.
+    ///
+    /// The later cycles of a shuffled file are walked in the current order, the instruction
+    /// sets skipped after a failure are walked, and only the loaded instruction set of
+    /// a streamed command list is walked.
+    pub fn commands(&self) -> impl Iterator<Item = String> + '_ {
+        CursorWalk::new(self).map(|cmd| cmd.to_string())
+    }
+
+    /// Count of the commands walked in one cycle by the executed instruction sets, without
+    /// jumps.
+    fn cycle_commands(&self) -> usize {
+        (0..self.auto_exc.exc_ins_assets.len())
+            .filter(|v| self.ins_selected(*v) && !self.ins_is_group(*v))
+            .map(|v| self.auto_exc.exc_ins_assets[v].commands_total())
+            .sum()
+    }
+
+    /// Count the total commands of the file run again from the current file position, after
+    /// the file cursor or the executed instruction sets changed. 0 for a streamed list.
+    fn progress_reset(&mut self) {
+        self.progress_total = match self.auto_exc.stream {
+            Some(_) => 0,
+            None => self.summary.commands + CursorWalk::new(self).count_left(),
+        };
+    }
+
+    /// Time the last command took, from the start of its read to the start of this one,
+    /// i.e. the wait for the input and the execution.
+    fn timing_poll(&mut self, now: Instant) {
+        let Some((cmd, start)) = self.timing.take() else {
+            return;
+        };
+        let elapsed = now.duration_since(start);
+        self.log_lock().timing_log(&cmd, elapsed);
+        if self.timings.len() == TIMINGS_MAX {
+            self.timings.pop_front();
+        }
+        self.timings.push_back((cmd, elapsed));
+    }
+
+    /// Recent commands and their time from the start of their read to the start of the next
+    /// read, the oldest first.
+    pub fn recent_timings(&self) -> Vec<(String, Duration)> {
+        self.timings.iter().cloned().collect()
+    }
+
+    /// (terminal, file) commands read, the terminal ones in the session and the file ones
+    /// since the last import.
+    pub fn command_counts(&self) -> (usize, usize) {
+        self.command_counts
+    }
+
+    /// (read, total) commands of the file run, counted from the import with the cycle times.
+    /// The total is 0 while the command list is streamed.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.summary.commands, self.progress_total)
+    }
+
+    /// The file command served by the next `read`: the pending command, or the pending
+    /// instruction if no command is pending. None outside a file run.
+    ///
+    /// Nothing is polled, the file cursor and the console state are left untouched.
+    pub fn peek_next(&self) -> Option<String> {
+        let next = match self.status.current {
+            ConsoleStatus::InsAcqFromFile | ConsoleStatus::InsExecFromFile => {
+                self.auto_exc.pending()
+            }
+            _ => None,
+        };
+        next.map(|cmd| cmd.to_string())
+    }
+
+    /// Instructions of the loaded instruction sets in file order, empty outside a file run.
+    /// The streamed command list holds only its current instruction set.
+    pub fn loaded_instructions(&self) -> Vec<String> {
+        self.auto_exc
+            .exc_ins_assets
+            .iter()
+            .map(|exc_assets| exc_assets.exc_ins.to_string())
+            .collect()
+    }
+
+    /// Count of the loaded instruction sets.
+    pub fn instruction_count(&self) -> usize {
+        self.auto_exc.exc_ins_assets.len()
+    }
+
+    /// In step mode, wait for the terminal enter unless `step` released the next file command.
+    fn step_gate(&mut self) {
+        if !self.step_wait {
+            return;
+        }
+        if let Some(next) = self.peek_next() {
+            self.file_log(&format!(" * 单步执行，按回车执行下一条命令 {}", next));
+        }
+        let _ = io::stdout().flush();
+        let mut input = String::new();
+        let _ = self.input.read_answer_line(&mut input);
+        self.step_wait = false;
+    }
+
+    /// Whether the console is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Record the state transition just made, dropping the oldest beyond the capacity.
+    fn history_push(&mut self) {
+        if self.history.len() == TRANSITION_HISTORY_MAX {
+            self.history.pop_front();
+        }
+        self.history
+            .push_back((self.status.previous.clone(), self.status.current.clone()));
+    }
+
+    /// The recent (previous, current) state transitions made by the state machine, oldest first.
+    pub fn transition_history(&self) -> Vec<(ConsoleStatus, ConsoleStatus)> {
+        self.history.iter().cloned().collect()
+    }
+
+    /// Save the execution progress of the file run in progress.
+    pub fn save_progress(&self, path: &Path) -> Result<(), DataError> {
+        let progress = ExecuteProgress {
+            next_exc_ins: self.auto_exc.next_exc_ins,
+            next_exc_cmd: self.auto_exc.next_exc_cmd,
+            cycle_times: self.auto_exc.cycle_times,
+            ins_cycle_times: self.auto_exc.ins_cycle_times,
+            order: self.auto_exc.order.clone(),
+        };
+        let context = toml::to_string(&progress)
+            .map_err(|err_info| DataError::Redaction(format!("执行进度保存失败：{}", err_info)))?;
+        std::fs::write(path, context)?;
+        Ok(())
+    }
+
+    /// Restore the execution progress saved by `save_progress`, called after the file is imported.
+    pub fn load_progress(&mut self, path: &Path) -> Result<(), DataError> {
+        let context = std::fs::read_to_string(path)?;
+        let progress = toml::from_str::<ExecuteProgress>(&context)
+            .map_err(|err_info| DataError::Parse(format!("执行进度文件格式有误：{}", err_info)))?;
+
+        let Some(ins_index) = progress.next_exc_ins else {
+            return Err(DataError::Redaction(
+                "执行进度中没有待执行的指令。".to_string(),
+            ));
+        };
+        if self.auto_exc.ins_at(ins_index).is_none() {
+            return Err(DataError::Redaction(format!(
+                "执行进度的第 {} 条主指令集超出已导入的指令集范围，请先导入对应的文件。",
+                ins_index + 1
+            )));
+        }
+        if let Some(cmd_index) = progress.next_exc_cmd {
+            if self.auto_exc.cmd_at(ins_index, cmd_index).is_none() {
+                return Err(DataError::Redaction(format!(
+                    "执行进度的第 {} 条子命令超出第 {} 条主指令集的子命令集范围。",
+                    cmd_index + 1,
+                    ins_index + 1
+                )));
+            }
+        }
+
+        let mut order = progress.order.clone();
+        order.sort_unstable();
+        if !progress.order.is_empty()
+            && !order
+                .iter()
+                .copied()
+                .eq(0..self.auto_exc.exc_ins_assets.len())
+        {
+            return Err(DataError::Redaction(
+                "执行进度的指令顺序与已导入的指令集不符，请先导入对应的文件。".to_string(),
+            ));
+        }
+
+        self.auto_exc.next_exc_ins = Some(ins_index);
+        self.auto_exc.next_exc_cmd = progress.next_exc_cmd;
+        self.auto_exc.cycle_times = progress.cycle_times;
+        self.auto_exc.ins_cycle_times = progress.ins_cycle_times;
+        self.auto_exc.order = progress.order;
+        self.ins_timing(ins_index);
+
+        // continue the file run from the restored command.
+        self.prompt_clear();
+        self.status.previous = self.status.current.clone();
+        self.status.current = match self.auto_exc.next_exc_cmd {
+            Some(_) => ConsoleStatus::InsExecFromFile,
+            None => ConsoleStatus::InsAcqFromFile,
+        };
+        Ok(())
+    }
+
+    /// Watch the imported file, and reload it when it is modified on disk during its file run.
+    /// The modified file is checked like an import, and its file run restarts from the first
+    /// command with new run statistics. Without a file run, nothing is reloaded.
+    pub fn enable_file_watch(&mut self) -> Result<(), DataError> {
+        let Some(file_address) = &self.auto_exc.file_address else {
+            return Err(DataError::Redaction(
+                "未导入文件，无法监听文件变化。".to_string(),
+            ));
+        };
+        let path = std::fs::canonicalize(file_address)?;
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)
+            .map_err(|err_info| DataError::Redaction(format!("文件监听创建失败：{}", err_info)))?;
+        // watch the directory, editors usually replace the file when saving.
+        watcher
+            .watch(
+                path.parent().unwrap_or(Path::new("/")),
+                RecursiveMode::NonRecursive,
+            )
+            .map_err(|err_info| DataError::Redaction(format!("文件监听创建失败：{}", err_info)))?;
+
+        self.watch = Some(FileWatch {
+            path,
+            _watcher: watcher,
+            events,
+        });
+        Ok(())
+    }
+
+    /// Reload the watched file if it has been modified.
+    /// The console status is kept while idle, a file run in progress restarts with the new commands.
+    fn file_watch_poll(&mut self) {
+        let Some(watch) = &self.watch else {
+            return;
+        };
+        // drain all the pending events.
+        let mut modified = false;
+        for event in watch.events.try_iter().flatten() {
+            modified |= (event.kind.is_modify() || event.kind.is_create())
+                && event.paths.contains(&watch.path);
+        }
+        // without a file run, the next import reads the modified file.
+        let (true, ConsoleStatus::InsAcqFromFile | ConsoleStatus::InsExecFromFile) =
+            (modified, &self.status.current)
+        else {
+            return;
+        };
+
+        let path = watch.path.clone();
+        let exc_file = match file_prepare(&path, &self.messages) {
+            Ok(exc_file) if exc_file.exc_ins_assets.is_empty() => {
+                Err(DataError::Redaction(self.messages.file_empty.to_string()))
+            }
+            other => other,
+        };
+        let previous = (
+            std::mem::take(&mut self.auto_exc),
+            self.run_only,
+            self.skip_ranges.clone(),
+        );
+        let armed = exc_file.and_then(|mut exc_file| {
+            exc_file.file_address = Some(path.display().to_string());
+            self.exc_arm(exc_file)
+        });
+        match armed {
+            Ok(()) => {
+                // the file run restarts from the first command of the modified file.
+                self.prompt_clear();
+                self.status.previous = self.status.current.clone();
+                self.status.current = ConsoleStatus::InsAcqFromFile;
+                let notice = format!(" * 文件 {} 已重新加载", path.display());
+                self.file_log(&notice);
+            }
+            // keep the previously loaded command set.
+            Err(err_info) => {
+                (self.auto_exc, self.run_only, self.skip_ranges) = previous;
+                self.err_log(&err_info);
+            }
+        }
+    }
+
     pub fn get_mac(&self) -> (bool, String) {
         if let Some(mac_expect) = self.auto_exc.mac.clone() {
             (true, mac_expect)
@@ -778,37 +2852,30 @@ where
     pub fn thread_interact(&self, sender: Sender<Result<(), ()>>) -> JoinHandle<()> {
         // the current t test has completed all cache instruction reads.
         let lag_time = self.t_expect_finish.0;
-        match self.log.lock().map_err(|_| {
-            DataError::Redaction("log information prints mutex acquisition failure.".to_string())
-        }) {
-            Ok(log) => log.file_exc_log(&format!(
-                " * {}{}",
-                match lag_time {
-                    0 => {
-                        "".to_string()
-                    }
-                    _ => {
-                        lag_time.to_string()
-                    }
-                },
-                match lag_time {
-                    0 => {
-                        "等待手动停止测试"
-                    }
-                    _ => {
-                        "ms 后自动停止测试"
-                    }
+        self.file_log(&format!(
+            " * {}{}",
+            match lag_time {
+                0 => {
+                    "".to_string()
+                }
+                _ => {
+                    lag_time.to_string()
+                }
+            },
+            match lag_time {
+                0 => {
+                    "等待手动停止测试"
+                }
+                _ => {
+                    "ms 后自动停止测试"
                 }
-            )),
-            Err(_err_info) => {
-                panic!("{}", _err_info);
             }
-        }
+        ));
 
         let stdin_thread = std::thread::spawn(move || match lag_time {
             0 => {
                 let mut input = String::from("");
-                if let Ok(_) = std::io::stdin().read_line(&mut input) {
+                if std::io::stdin().read_line(&mut input).is_ok() {
                     let _ = sender.send(Ok(()));
                 }
             }
@@ -818,22 +2885,481 @@ where
             }
         });
 
-        match self.log.lock().map_err(|_| {
-            DataError::Redaction("log information prints mutex acquisition failure.".to_string())
-        }) {
-            Ok(log) => log.file_exc_log(&format!("测试流程已退出。")),
-            Err(_err_info) => {
-                panic!("{}", _err_info);
+        self.file_log("测试流程已退出。");
+
+        stdin_thread
+    }
+
+    /// treat the instruction sets without commands as grouping nodes, which are skipped rather than executed.
+    pub fn set_group_only_instructions(&mut self, group_only: bool) {
+        self.group_only = group_only;
+    }
+
+    /// Execute only the instruction sets with one of the tags, the others are skipped.
+    /// No tags execute all of them.
+    pub fn set_tag_filter(&mut self, tags: &[&str]) {
+        self.tag_filter = tags.iter().map(|v| v.to_string()).collect();
+    }
+
+    /// Execute only the selected instruction set of the loaded file run, once with its commands,
+    /// then finish the file run. The tag filter still applies.
+    pub fn run_only(&mut self, selector: InstructionSelector) -> Result<(), DataError> {
+        if self.auto_exc.next_exc_ins.is_none() || self.auto_exc.stream.is_some() {
+            return Err(DataError::Redaction(
+                "没有导入的主指令集，请先导入文件。".to_string(),
+            ));
+        }
+        let ins_index = match &selector {
+            InstructionSelector::Index(ins_index) => {
+                Some(*ins_index).filter(|v| *v < self.auto_exc.exc_ins_assets.len())
             }
+            InstructionSelector::Label(label) => self.auto_exc.labels.get(label).copied(),
+        };
+        let Some(ins_index) = ins_index else {
+            return Err(DataError::Redaction(match selector {
+                InstructionSelector::Index(ins_index) => {
+                    format!("第 {} 条主指令集不存在。", ins_index + 1)
+                }
+                InstructionSelector::Label(label) => format!("标签 {} 的主指令集不存在。", label),
+            }));
+        };
+
+        self.run_only = Some(ins_index);
+        self.auto_exc.cycle_times = Some(1);
+        self.auto_exc.order.clear();
+        self.served = None;
+        self.retry = None;
+        self.deadline = None;
+        self.ins_advance(0)?;
+        self.progress_reset();
+        if let ConsoleStatus::InsAcqFromFile | ConsoleStatus::InsExecFromFile = self.status.current
+        {
+            self.refresh()?;
         }
+        Ok(())
+    }
 
-        stdin_thread
+    /// Skip the instruction sets of the index ranges in the loaded file run, e.g. `5..10`,
+    /// until it ends. The ranges replace the previous ones, no ranges execute all of them.
+    pub fn set_skip_ranges(&mut self, ranges: Vec<Range<usize>>) -> Result<(), DataError> {
+        let len = self.auto_exc.exc_ins_assets.len();
+        if let Some(range) = ranges.iter().find(|v| v.start > v.end || v.end > len) {
+            return Err(DataError::Redaction(format!(
+                "跳过范围 {:?} 无效，主指令集共 {} 条。",
+                range, len
+            )));
+        }
+
+        self.skip_ranges = ranges;
+
+        // the pending instruction set not begun yet is skipped too.
+        if let (ConsoleStatus::InsAcqFromFile, Some(ins_index)) =
+            (&self.status.current, self.auto_exc.next_exc_ins)
+        {
+            if !self.ins_selected(ins_index) {
+                self.ins_advance(self.ins_pos(ins_index))?;
+                self.refresh()?;
+            }
+        }
+        self.progress_reset();
+        Ok(())
+    }
+
+    /// Whether the instruction set of the index is executed by the file run.
+    fn ins_selected(&self, ins_index: usize) -> bool {
+        if self.run_only.is_some_and(|v| v != ins_index) {
+            return false;
+        }
+        if self.skip_ranges.iter().any(|v| v.contains(&ins_index)) {
+            return false;
+        }
+        if self.tag_filter.is_empty() {
+            return true;
+        }
+        self.auto_exc.exc_ins_assets[ins_index]
+            .tags
+            .iter()
+            .flatten()
+            .any(|v| self.tag_filter.contains(v))
+    }
+
+    /// mark the result of the last executed command, instruction sets with `skip_if_prev_failed`
+    /// are skipped after a failure.
+    pub fn set_last_result(&mut self, success: bool) {
+        if let Some(deadline) = self.deadline.take().filter(|v| v.expired()) {
+            if let Err(err_info) = self.timeout_fail(&deadline) {
+                self.err_log(&err_info);
+            }
+            return;
+        }
+        self.last_result = success;
+        if !success && self.run_start.is_some() {
+            self.summary.failures += 1;
+        }
+        if !success {
+            self.retry_arm();
+        }
+    }
+
+    /// The last file command reported no result in time, fail it on the next read.
+    fn deadline_poll(&mut self) -> Result<(), DataError> {
+        let Some(deadline) = self.deadline.take().filter(|v| v.expired()) else {
+            return Ok(());
+        };
+        self.timeout_fail(&deadline)?;
+        self.err_log(deadline.error());
+        Ok(())
+    }
+
+    /// Fail the timed out file command, in strict mode the file run is aborted
+    /// once its retries are used up.
+    fn timeout_fail(&mut self, deadline: &Deadline) -> Result<(), DataError> {
+        self.last_result = false;
+        if self.run_start.is_some() {
+            self.summary.failures += 1;
+        }
+        let retried = self.retry_arm();
+        if self.strict_expect && !retried {
+            self.abort()?;
+            return Err(deadline.error());
+        }
+        Ok(())
+    }
+
+    /// Re-issue the last file command on the next read if it has retries left.
+    fn retry_arm(&mut self) -> bool {
+        match self.retry.as_mut().filter(|v| v.left != 0 && !v.pending) {
+            Some(retry) => {
+                retry.left -= 1;
+                retry.pending = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The file command to re-issue, after the delay of its instruction set.
+    fn retry_next(&mut self) -> Option<String> {
+        let retry = self.retry.as_mut().filter(|v| v.pending)?;
+        retry.pending = false;
+        let (cmd, left, delay) = (retry.cmd.clone(), retry.left, retry.delay);
+        self.served = retry.served.clone();
+        self.deadline = retry.deadline.clone().map(|v| Deadline {
+            start: Instant::now(),
+            ..v
+        });
+        self.summary.retries += 1;
+
+        if delay != 0 {
+            self.file_log(&format!(" * 延时等待 {} ms", delay));
+            thread::sleep(Duration::from_millis(delay as u64));
+        }
+        self.file_log(&format!(" * 重试命令 {}，剩余重试 {} 次", cmd, left));
+        self.file_log(&cmd);
+        self.event_send(ConsoleEvent::FileExec(cmd.clone()));
+        Some(cmd)
+    }
+
+    /// Report the actual output of the last command read from the file, it is compared with
+    /// the `expect` of the command and the result is recorded. Commands without `expect`, or
+    /// already reported, are not recorded, `None` is returned.
+    ///
+    /// Mismatches accumulate, in strict mode the first mismatch aborts the file run instead.
+    /// An output reported after the timeout of the command fails it with `DataError::Timeout`.
+    pub fn report_output(&mut self, actual: &str) -> Result<Option<bool>, DataError> {
+        if let Some(deadline) = self.deadline.take().filter(|v| v.expired()) {
+            self.served = None;
+            self.timeout_fail(&deadline)?;
+            return Err(deadline.error());
+        }
+        let Some(mut result) = self.served.take() else {
+            return Ok(None);
+        };
+
+        let passed = result.expect.trim() == actual.trim();
+        result.actual = actual.to_string();
+        result.passed = passed;
+        match passed {
+            true => self.summary.passed += 1,
+            false => {
+                self.summary.failed += 1;
+                self.summary
+                    .failing
+                    .push((result.ins_index, result.cmd_index));
+            }
+        }
+        let mismatch = Messages::fill(
+            self.messages.expect_mismatch,
+            &[
+                &(result.ins_index + 1),
+                &(result.cmd_index + 1),
+                &actual.trim(),
+                &result.expect.trim(),
+            ],
+        );
+        self.expect_results.push(result);
+
+        if !passed {
+            let retried = self.retry_arm();
+            if self.strict_expect && !retried {
+                self.abort()?;
+                return Err(DataError::Redaction(mismatch));
+            }
+            self.file_log(&mismatch);
+        }
+        Ok(Some(passed))
+    }
+
+    /// Abort the file run on the first output mismatch reported by `report_output`, or the first
+    /// result timeout, once the retries of the command are used up.
+    pub fn set_strict_expect(&mut self, strict: bool) {
+        self.strict_expect = strict;
+    }
+
+    /// Results of the outputs reported since the file import.
+    pub fn expect_results(&self) -> &[ExpectResult] {
+        &self.expect_results
+    }
+
+    /// Count a command read from the file, and emit the run summary once all commands are read.
+    fn run_track(&mut self, success: bool) {
+        if self.run_start.is_none() {
+            return;
+        }
+        self.summary.commands += 1;
+        if !success {
+            self.summary.failures += 1;
+        }
+        self.log_lock()
+            .progress_log(self.summary.commands, self.progress_total);
+
+        if self.auto_exc.next_exc_ins.is_none() {
+            self.run_finish();
+        }
+    }
+
+    /// Emit the run summary of the file run once it has ended.
+    fn run_finish(&mut self) {
+        let Some(run_start) = self.run_start.take() else {
+            return;
+        };
+        self.summary.duration = run_start.elapsed();
+        self.expect_pending = true;
+        self.log_lock().run_summary_log(&self.summary);
+        self.run_log = None;
+    }
+
+    /// Summary of the file run in progress, or of the last one.
+    pub fn run_summary(&self) -> RunSummary {
+        self.summary.clone()
+    }
+
+    /// Emit the expectation summary of the finished run once its last output could be reported,
+    /// i.e. on the next read.
+    fn expect_summary_poll(&mut self) {
+        // the re-issued last command reports its output first.
+        if self.retry.as_ref().is_some_and(|v| v.pending) {
+            return;
+        }
+        if !std::mem::take(&mut self.expect_pending) {
+            return;
+        }
+        if self.summary.passed + self.summary.failed != 0 {
+            self.log_lock().expect_summary_log(&self.summary);
+        }
+    }
+
+    /// limit how many recent input segments are echoed after the main prompt, 0 means unlimited.
+    pub fn set_echo_depth(&mut self, depth: usize) {
+        self.interact.echo_depth = depth;
+    }
+
+    /// Limit how many recent input segments are kept at all, the older ones are dropped and
+    /// elided in the prompt, 0 means unlimited.
+    pub fn set_max_prompt_depth(&mut self, depth: usize) {
+        self.interact.max_depth = depth;
+        self.prompt_trim();
+    }
+
+    /// Append the accepted input segment to the prompt.
+    fn prompt_push(&mut self, segment: String) {
+        self.interact.sub_prompt.push(segment);
+        self.prompt_trim();
+    }
+
+    /// Drop the input segments beyond the kept depth.
+    fn prompt_trim(&mut self) {
+        let segments = &mut self.interact.sub_prompt;
+        if self.interact.max_depth != 0 && segments.len() > self.interact.max_depth {
+            segments.drain(..segments.len() - self.interact.max_depth);
+            self.interact.elided = true;
+        }
+    }
+
+    /// Render the file position of the next command read during a file run instead of the
+    /// echoed input segments numbered from 1, e.g. `> main[3] > calibrate[1] > `, with the label of the
+    /// instruction set when it has one.
+    pub fn set_breadcrumb(&mut self, breadcrumb: bool) {
+        self.interact.breadcrumb = breadcrumb;
+    }
+
+    /// File position of the next command read, None outside a file run.
+    fn breadcrumb(&self) -> Option<String> {
+        match self.status.current {
+            ConsoleStatus::InsAcqFromFile | ConsoleStatus::InsExecFromFile => {}
+            _ => return None,
+        }
+        let ins_index = self.auto_exc.next_exc_ins?;
+        let assets = self.auto_exc.exc_ins_assets.get(ins_index)?;
+        let mut crumb = match &assets.label {
+            Some(label) => format!("{}[{}] > ", label, ins_index + 1),
+            None => format!("{}[{}] > ", assets.exc_ins, ins_index + 1),
+        };
+        if let Some(cmd_index) = self.auto_exc.next_exc_cmd {
+            if let Some(cmd) = self.auto_exc.cmd_at(ins_index, cmd_index) {
+                crumb.push_str(&format!("{}[{}] > ", cmd, cmd_index + 1));
+            }
+        }
+        Some(crumb)
+    }
+
+    /// Log the prompt of the read followed by the extra prompt line, if any.
+    fn prompt_show(&self, prompt: &str) {
+        let prompt_info = if prompt.is_empty() {
+            self.prompt_echo()
+        } else {
+            format!("{}\r\n{}", self.prompt_echo(), prompt)
+        };
+        self.prompt_log(&self.paint(color::CYAN, &prompt_info).to_string());
+        self.event_send(ConsoleEvent::Prompt(prompt_info));
+    }
+
+    /// Render the main prompt followed by the echoed input segments.
+    fn prompt_echo(&self) -> String {
+        let segments = &self.interact.sub_prompt;
+        let mut echo = self.interact.main_prompt.clone();
+        if self.interact.breadcrumb {
+            if let Some(crumb) = self.breadcrumb() {
+                echo.push_str(&crumb);
+                return echo;
+            }
+        }
+        let skip = match self.interact.echo_depth {
+            0 => 0,
+            depth => segments.len().saturating_sub(depth),
+        };
+        if skip != 0 || self.interact.elided {
+            echo.push_str("... > ");
+        }
+        for segment in &segments[skip..] {
+            echo.push_str(&format!("{} > ", segment));
+        }
+        echo
+    }
+
+    /// Log the file command execution information with the timestamp.
+    fn file_log(&self, log_info: &str) {
+        let log = self.log_lock();
+        log.file_exc_log(&self.stamp(&*log, log_info));
+    }
+
+    /// Log the terminal command execution information with the timestamp.
+    fn terminal_log(&self, log_info: &str) {
+        let log = self.log_lock();
+        log.terminal_exc_log(&self.stamp(&*log, log_info));
+    }
+
+    /// Log the internal tracing information with the timestamp, when verbose.
+    fn debug_log(&self, log_info: &str) {
+        if !self.verbose {
+            return;
+        }
+        let log = self.log_lock();
+        log.debug_log(&self.stamp(&*log, log_info));
+    }
+
+    /// Open the log file of the file run before its first command is read, when the run log
+    /// directory is set. It is closed by the run summary.
+    fn run_log_open(&mut self) {
+        let starting = self.run_start.is_some() && self.summary.commands == 0;
+        let (Some(dir), true) = (&self.run_log_dir, starting && self.run_log.is_none()) else {
+            return;
+        };
+        let timestamp = self.log_lock().timestamp();
+        match RunLog::create(dir, &timestamp) {
+            Ok(run_log) => self.run_log = Some(run_log),
+            Err(err_info) => {
+                let err_info = format!("运行日志创建失败：{}", err_info);
+                self.err_log(&err_info);
+            }
+        }
+    }
+
+    /// Log the error in red.
+    fn err_log<E>(&self, err_info: E)
+    where
+        E: fmt::Display + fmt::Debug,
+    {
+        self.log_lock().err_log(self.paint(color::RED, &err_info));
+        self.event_send(ConsoleEvent::Error(err_info.to_string()));
+    }
+
+    /// Send the event on the event channel, if any.
+    fn event_send(&self, event: ConsoleEvent) {
+        if let Some(events) = &self.events {
+            let _ = events.send(event);
+        }
+    }
+
+    /// Log the prompt with the timestamp, unless quiet.
+    fn prompt_log(&self, log_info: &str) {
+        if self.quiet {
+            return;
+        }
+        self.reply_log(log_info);
+    }
+
+    /// Log the reply to an explicit request through the prompt log with the timestamp, even
+    /// when quiet, e.g. the `help` answer and the `select` options.
+    fn reply_log(&self, log_info: &str) {
+        let log = self.log_lock();
+        log.prompt_log(&self.stamp(&*log, log_info));
+    }
+
+    /// Prepend the log timestamp to the log information when enabled.
+    fn stamp(&self, log: &T, log_info: &str) -> String {
+        match self.log_timestamps {
+            true => format!("{} {}", log.timestamp(), log_info),
+            false => log_info.to_string(),
+        }
+    }
+
+    /// Wrap the log value in the ANSI color code when colors are enabled.
+    fn paint<V>(&self, code: &'static str, value: V) -> Paint<V> {
+        Paint {
+            code,
+            value,
+            enabled: self.color,
+        }
+    }
+
+    /// Acquire the log, recovering it from a poisoned mutex.
+    fn log_lock(&self) -> LogTee<'_, T> {
+        // a panic while logging poisons the mutex, the log itself is still usable.
+        LogTee {
+            log: self
+                .log
+                .lock()
+                .unwrap_or_else(|err_info| err_info.into_inner()),
+            run_log: self.run_log.as_ref(),
+        }
     }
 
     /// Clear the console command cache.
     fn prompt_clear(&mut self) {
-        self.interact.main_prompt = String::from("> ");
-        self.interact.sub_prompt = String::from("");
+        self.interact.main_prompt = self.interact.default_prompt.clone();
+        self.interact.sub_prompt.clear();
+        self.interact.elided = false;
     }
 
     fn exc_clear(&mut self) {
@@ -841,8 +3367,16 @@ where
         self.auto_exc.exc_ins_assets.clear();
         self.auto_exc.cycle_times = None;
         self.auto_exc.mac = None;
+        self.auto_exc.includes = None;
+        self.auto_exc.shuffle = None;
+        self.auto_exc.ins_cycle_times = None;
+        self.auto_exc.labels.clear();
+        self.auto_exc.order.clear();
+        self.auto_exc.stream = None;
         self.auto_exc.next_exc_cmd = None;
         self.auto_exc.next_exc_ins = None;
+        self.run_only = None;
+        self.skip_ranges.clear();
     }
 
     fn check_reset(&mut self) {
@@ -0,0 +1,200 @@
+use crate::interface::{self, ConsoleInput};
+use crossterm::cursor::MoveToColumn;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::Print;
+use crossterm::terminal::{self, Clear, ClearType};
+use crossterm::QueueableCommand;
+use std::collections::VecDeque;
+use std::io::{self, IsTerminal, Write};
+
+const HISTORY_MAX: usize = 100;
+
+/// Terminal input with raw-mode line editing: backspace, delete, left/right, Home/End,
+/// Ctrl-A/Ctrl-E, and up/down through the submitted lines. Ctrl-D on an empty line closes
+/// the input.
+///
+/// It is the default terminal input with the `readline` feature, when stdin is not a TTY
+/// the lines are read as they are.
+///
+/// The history is only kept in memory, the secrets read without echo and the answers of the
+/// interactive prompts are never recorded.
+#[derive(Debug)]
+pub struct LineEditor {
+    history: VecDeque<String>, // submitted lines, the oldest first.
+    history_enabled: bool,     // the submitted lines are recorded.
+}
+
+impl Default for LineEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineEditor {
+    pub fn new() -> Self {
+        LineEditor {
+            history: VecDeque::with_capacity(HISTORY_MAX),
+            history_enabled: true,
+        }
+    }
+
+    /// Append the submitted line to the history, a recalled line is appended again.
+    fn history_push(&mut self, line: &str) {
+        if !self.history_enabled || line.trim().is_empty() {
+            return;
+        }
+        if self.history.len() == HISTORY_MAX {
+            self.history.pop_front();
+        }
+        self.history.push_back(line.to_string());
+    }
+
+    /// Edit a line in raw mode until enter, None when the input is closed. The submitted line
+    /// is recorded in the history if asked.
+    fn edit(&mut self, record: bool) -> io::Result<Option<String>> {
+        let _raw = RawMode::enable()?;
+        let mut stdout = io::stdout();
+        let mut line: Vec<char> = Vec::new();
+        let mut cursor = 0;
+        // history index of the recalled line, and the line edited before the recall.
+        let mut recalled: Option<usize> = None;
+        let mut draft: Vec<char> = Vec::new();
+
+        loop {
+            let Event::Key(KeyEvent {
+                code,
+                modifiers,
+                kind: KeyEventKind::Press,
+                ..
+            }) = event::read()?
+            else {
+                continue;
+            };
+            match (code, modifiers.contains(KeyModifiers::CONTROL)) {
+                (KeyCode::Enter, _) | (KeyCode::Char('j'), true) => break,
+                (KeyCode::Char('c'), true) => {
+                    stdout.write_all(b"\r\n")?;
+                    return Err(io::Error::from(io::ErrorKind::Interrupted));
+                }
+                (KeyCode::Char('d'), true) if line.is_empty() => {
+                    stdout.write_all(b"\r\n")?;
+                    return Ok(None);
+                }
+                (KeyCode::Char('a'), true) | (KeyCode::Home, _) => cursor = 0,
+                (KeyCode::Char('e'), true) | (KeyCode::End, _) => cursor = line.len(),
+                (KeyCode::Char(c), false) => {
+                    line.insert(cursor, c);
+                    cursor += 1;
+                }
+                (KeyCode::Backspace, _) if cursor > 0 => {
+                    cursor -= 1;
+                    line.remove(cursor);
+                }
+                (KeyCode::Delete, _) if cursor < line.len() => {
+                    line.remove(cursor);
+                }
+                (KeyCode::Left, _) => cursor = cursor.saturating_sub(1),
+                (KeyCode::Right, _) => cursor = (cursor + 1).min(line.len()),
+                (KeyCode::Up, _) if !self.history.is_empty() => {
+                    let index = match recalled {
+                        Some(index) => index.saturating_sub(1),
+                        None => {
+                            draft = std::mem::take(&mut line);
+                            self.history.len() - 1
+                        }
+                    };
+                    recalled = Some(index);
+                    line = self.history[index].chars().collect();
+                    cursor = line.len();
+                }
+                (KeyCode::Down, _) if recalled.is_some() => {
+                    match recalled.filter(|v| v + 1 < self.history.len()) {
+                        Some(index) => {
+                            recalled = Some(index + 1);
+                            line = self.history[index + 1].chars().collect();
+                        }
+                        None => {
+                            recalled = None;
+                            line = std::mem::take(&mut draft);
+                        }
+                    }
+                    cursor = line.len();
+                }
+                _ => continue,
+            }
+            render(&mut stdout, &line, cursor)?;
+        }
+
+        stdout.write_all(b"\r\n")?;
+        stdout.flush()?;
+        let line: String = line.into_iter().collect();
+        if record {
+            self.history_push(&line);
+        }
+        Ok(Some(line))
+    }
+
+    /// Read an edited line into the buffer, or the stdin line when it is not a terminal.
+    fn edit_line(&mut self, buf: &mut String, record: bool) -> io::Result<usize> {
+        if !io::stdin().is_terminal() {
+            return io::stdin().read_line(buf);
+        }
+        match self.edit(record)? {
+            Some(line) => {
+                buf.push_str(&line);
+                buf.push('\n');
+                Ok(line.len() + 1)
+            }
+            None => Ok(0),
+        }
+    }
+}
+
+/// Redraw the edited line and place the cursor.
+fn render(stdout: &mut io::Stdout, line: &[char], cursor: usize) -> io::Result<()> {
+    let line: String = line.iter().collect();
+    stdout
+        .queue(MoveToColumn(0))?
+        .queue(Clear(ClearType::UntilNewLine))?
+        .queue(Print(line))?
+        .queue(MoveToColumn(cursor as u16))?;
+    stdout.flush()
+}
+
+impl ConsoleInput for LineEditor {
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        self.edit_line(buf, true)
+    }
+
+    fn read_answer_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        self.edit_line(buf, false)
+    }
+
+    fn read_secret_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        interface::stdin_secret_line(buf)
+    }
+
+    fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    fn set_history_enabled(&mut self, enabled: bool) {
+        self.history_enabled = enabled;
+    }
+}
+
+/// Raw mode of the terminal, restored when dropped.
+struct RawMode;
+
+impl RawMode {
+    fn enable() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        Ok(RawMode)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+    }
+}
@@ -0,0 +1,69 @@
+use std::fmt;
+
+/// User-facing text of the console, the default set is Chinese.
+///
+/// The `{}` placeholders of a message are filled with its context in order,
+/// e.g. the file path or the instruction set number counted from 1.
+#[derive(Debug, Clone)]
+pub struct Messages {
+    pub file_address: &'static str, // prompt of the automatic execution file address input.
+    pub file_malformed: &'static str, // guidance when the automatic execution file content is malformed.
+    pub file_cyclic_include: &'static str, // {file path} included by itself.
+    pub file_empty: &'static str,     // the automatic execution file has no instruction set.
+    pub label_duplicate: &'static str, // {instruction set number} {label} defined twice.
+    pub label_undefined: &'static str, // {instruction set number} {label} jumped to but not defined.
+    pub goto_undefined: &'static str,  // {label} jumped to at run time but not defined.
+    pub ins_polluted: &'static str,    // no instruction set to poll from the imported file.
+    pub ins_lost: &'static str,        // the instruction set of the pending command is missing.
+    pub ins_read_failed: &'static str, // the pending instruction set is missing.
+    pub cmd_lost: &'static str,        // the command set of the pending instruction set is missing.
+    pub cmd_out_of_range: &'static str, // {instruction set number} {command number} the pending command is missing.
+    pub order_inconsistent: &'static str, // the execution order does not match the instruction sets.
+    pub label_dangling: &'static str,     // {label} points at a missing instruction set.
+    pub cycle_times_exceeded: &'static str, // {cycle times} {bound} cycle times above the bound.
+    pub expect_mismatch: &'static str, // {instruction set number} {command number} {actual} {expect} output mismatch.
+    pub command_denied: &'static str,  // {command} denied, or not allowed.
+}
+
+impl Messages {
+    /// Fill the `{}` placeholders of the message with the context in order.
+    pub fn fill(message: &str, context: &[&dyn fmt::Display]) -> String {
+        let mut context = context.iter();
+        let mut pieces = message.split("{}");
+        let mut filled = pieces.next().unwrap_or_default().to_string();
+        for piece in pieces {
+            if let Some(value) = context.next() {
+                filled.push_str(&value.to_string());
+            }
+            filled.push_str(piece);
+        }
+        filled
+    }
+}
+
+impl Default for Messages {
+    fn default() -> Self {
+        Messages {
+            file_address: "请输入文件地址",
+            file_malformed: "文件内容格式有误，检查文件内容是否满足： \
+                - 文件涉及测试组 执行次数 <可选，若未输入默认执行一次>  \
+                - 单次测试 主指令 <必须>  \
+                - 单次测试 子命令/子命令集 <可选>",
+            file_cyclic_include: "文件 {} 被循环包含，请检查 includes 配置。",
+            file_empty: "文件未包含任何主指令集。",
+            label_duplicate: "第 {} 条主指令集的标签 {} 重复定义。",
+            label_undefined: "第 {} 条主指令集跳转的标签 {} 未定义。",
+            goto_undefined: "跳转的标签 {} 未定义，请重新导入文件开始测试。",
+            ins_polluted: "获取第一条主指令集失败，文件导入的指令集内容被污染，请重新导入文件。",
+            ins_lost: "子命令的主指令意外丢失，请重新导入文件开始测试。",
+            ins_read_failed: "读取指定主指令集失败，请重新导入文件开始测试。",
+            cmd_lost: "指定主指令集的子命令集意外丢失，请重新导入文件开始测试。",
+            cmd_out_of_range: "第 {} 条主指令集的第 {} 条子命令超出范围，请重新导入文件开始测试。",
+            order_inconsistent: "执行顺序与主指令集不一致，请重新导入文件开始测试。",
+            label_dangling: "标签 {} 指向的主指令集不存在，请重新导入文件开始测试。",
+            cycle_times_exceeded: "执行次数 {} 超出上限 {}。",
+            expect_mismatch: "第 {} 条主指令集的第 {} 条子命令输出 {} 与期望输出 {} 不符。",
+            command_denied: "命令 {} 不允许执行。",
+        }
+    }
+}
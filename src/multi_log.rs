@@ -0,0 +1,105 @@
+use crate::interface::{ConsoleLog, ErrInfo, LogLevel};
+use crate::RunSummary;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
+
+/// Console log forwarding every log information to each of its logs in order, e.g. to stdout
+/// and a file at once, used like any other log through `Arc<Mutex<MultiLog>>`.
+///
+/// The errors are forwarded through `err_dyn_log`. A log panicking is skipped, the following
+/// logs still receive the log information.
+#[derive(Default)]
+pub struct MultiLog {
+    logs: Vec<Box<dyn ConsoleLog>>,
+}
+
+impl MultiLog {
+    pub fn new(logs: Vec<Box<dyn ConsoleLog>>) -> Self {
+        MultiLog { logs }
+    }
+
+    /// Append the log, e.g. `MultiLog::default().with(Box::new(log))`.
+    pub fn with(mut self, log: Box<dyn ConsoleLog>) -> Self {
+        self.logs.push(log);
+        self
+    }
+
+    /// Forward to each log, isolating the panics of each one.
+    fn each(&self, f: impl Fn(&dyn ConsoleLog)) {
+        for log in &self.logs {
+            if panic::catch_unwind(AssertUnwindSafe(|| f(log.as_ref()))).is_err() {
+                eprintln!("日志输出异常，已跳过。");
+            }
+        }
+    }
+}
+
+impl ConsoleLog for MultiLog {
+    fn log(&self, level: LogLevel, log_info: &str) {
+        self.each(|log| log.log(level, log_info));
+    }
+
+    fn prompt_log(&self, log_info: &str) {
+        self.each(|log| log.prompt_log(log_info));
+    }
+
+    fn file_exc_log(&self, log_info: &str) {
+        self.each(|log| log.file_exc_log(log_info));
+    }
+
+    fn terminal_exc_log(&self, log_info: &str) {
+        self.each(|log| log.terminal_exc_log(log_info));
+    }
+
+    fn debug_log(&self, log_info: &str) {
+        self.each(|log| log.debug_log(log_info));
+    }
+
+    fn err_dyn_log(&self, err_info: &dyn ErrInfo) {
+        self.each(|log| log.err_dyn_log(err_info));
+    }
+
+    fn state_log(&self, prev: &str, next: &str) {
+        self.each(|log| log.state_log(prev, next));
+    }
+
+    fn run_summary_log(&self, summary: &RunSummary) {
+        self.each(|log| log.run_summary_log(summary));
+    }
+
+    fn breakpoint_log(&self, ins_index: usize) {
+        self.each(|log| log.breakpoint_log(ins_index));
+    }
+
+    fn desc_log(&self, ins_index: usize, desc: &str) {
+        self.each(|log| log.desc_log(ins_index, desc));
+    }
+
+    fn timing_log(&self, cmd: &str, elapsed: Duration) {
+        self.each(|log| log.timing_log(cmd, elapsed));
+    }
+
+    fn progress_log(&self, read: usize, total: usize) {
+        self.each(|log| log.progress_log(read, total));
+    }
+
+    fn expect_summary_log(&self, summary: &RunSummary) {
+        self.each(|log| log.expect_summary_log(summary));
+    }
+
+    /// The timestamp of the first log.
+    fn timestamp(&self) -> String {
+        match self.logs.first() {
+            Some(log) => log.timestamp(),
+            None => String::new(),
+        }
+    }
+
+    /// The invalid input notice of the first log.
+    fn err_invalid(&self) -> &'static str {
+        match self.logs.first() {
+            Some(log) => log.err_invalid(),
+            None => "invalid input.",
+        }
+    }
+}
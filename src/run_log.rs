@@ -0,0 +1,168 @@
+use crate::color;
+use crate::interface::{ConsoleLog, ErrInfo, LogLevel};
+use crate::RunSummary;
+use std::cell::RefCell;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Write};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::MutexGuard;
+use std::time::Duration;
+
+/// Log file of a single file run, `<dir>/run-<timestamp>.log`.
+#[derive(Debug)]
+pub(crate) struct RunLog {
+    path: PathBuf,
+    file: RefCell<File>,
+}
+
+impl RunLog {
+    /// Create the log file of the run started at the timestamp in the directory.
+    pub(crate) fn create(dir: &Path, timestamp: &str) -> io::Result<Self> {
+        // the colons of the timestamp are not allowed in every file system.
+        let path = dir.join(format!("run-{}.log", timestamp.replace(':', "-")));
+        let file = File::create(&path)?;
+        Ok(RunLog {
+            path,
+            file: RefCell::new(file),
+        })
+    }
+}
+
+impl ConsoleLog for RunLog {
+    fn log(&self, _level: LogLevel, log_info: &str) {
+        if let Err(err_info) = writeln!(self.file.borrow_mut(), "{}", color::strip(log_info)) {
+            eprintln!("运行日志 {} 写入失败：{}", self.path.display(), err_info);
+        }
+    }
+
+    fn terminal_exc_log(&self, log_info: &str) {
+        self.log(LogLevel::Info, log_info);
+    }
+}
+
+/// Acquired console log, every log information is tee'd to the log file of the file run.
+pub(crate) struct LogTee<'a, T> {
+    pub(crate) log: MutexGuard<'a, T>,
+    pub(crate) run_log: Option<&'a RunLog>,
+}
+
+impl<T> Deref for LogTee<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.log
+    }
+}
+
+impl<T: ConsoleLog> ConsoleLog for LogTee<'_, T> {
+    fn log(&self, level: LogLevel, log_info: &str) {
+        self.log.log(level, log_info);
+        if let Some(run_log) = self.run_log {
+            run_log.log(level, log_info);
+        }
+    }
+
+    fn prompt_log(&self, log_info: &str) {
+        self.log.prompt_log(log_info);
+        if let Some(run_log) = self.run_log {
+            run_log.prompt_log(log_info);
+        }
+    }
+
+    fn file_exc_log(&self, log_info: &str) {
+        self.log.file_exc_log(log_info);
+        if let Some(run_log) = self.run_log {
+            run_log.file_exc_log(log_info);
+        }
+    }
+
+    fn terminal_exc_log(&self, log_info: &str) {
+        self.log.terminal_exc_log(log_info);
+        if let Some(run_log) = self.run_log {
+            run_log.terminal_exc_log(log_info);
+        }
+    }
+
+    fn debug_log(&self, log_info: &str) {
+        self.log.debug_log(log_info);
+        if let Some(run_log) = self.run_log {
+            run_log.debug_log(log_info);
+        }
+    }
+
+    fn err_log<E>(&self, err_info: E)
+    where
+        E: fmt::Display + fmt::Debug,
+    {
+        self.log.err_log(&err_info);
+        if let Some(run_log) = self.run_log {
+            run_log.err_log(&err_info);
+        }
+    }
+
+    fn err_dyn_log(&self, err_info: &dyn ErrInfo) {
+        self.log.err_dyn_log(err_info);
+        if let Some(run_log) = self.run_log {
+            run_log.err_dyn_log(err_info);
+        }
+    }
+
+    fn state_log(&self, prev: &str, next: &str) {
+        self.log.state_log(prev, next);
+        if let Some(run_log) = self.run_log {
+            run_log.state_log(prev, next);
+        }
+    }
+
+    fn run_summary_log(&self, summary: &RunSummary) {
+        self.log.run_summary_log(summary);
+        if let Some(run_log) = self.run_log {
+            run_log.run_summary_log(summary);
+        }
+    }
+
+    fn breakpoint_log(&self, ins_index: usize) {
+        self.log.breakpoint_log(ins_index);
+        if let Some(run_log) = self.run_log {
+            run_log.breakpoint_log(ins_index);
+        }
+    }
+
+    fn desc_log(&self, ins_index: usize, desc: &str) {
+        self.log.desc_log(ins_index, desc);
+        if let Some(run_log) = self.run_log {
+            run_log.desc_log(ins_index, desc);
+        }
+    }
+
+    fn timing_log(&self, cmd: &str, elapsed: Duration) {
+        self.log.timing_log(cmd, elapsed);
+        if let Some(run_log) = self.run_log {
+            run_log.timing_log(cmd, elapsed);
+        }
+    }
+
+    fn progress_log(&self, read: usize, total: usize) {
+        self.log.progress_log(read, total);
+        if let Some(run_log) = self.run_log {
+            run_log.progress_log(read, total);
+        }
+    }
+
+    fn expect_summary_log(&self, summary: &RunSummary) {
+        self.log.expect_summary_log(summary);
+        if let Some(run_log) = self.run_log {
+            run_log.expect_summary_log(summary);
+        }
+    }
+
+    fn timestamp(&self) -> String {
+        self.log.timestamp()
+    }
+
+    fn err_invalid(&self) -> &'static str {
+        self.log.err_invalid()
+    }
+}
@@ -0,0 +1,82 @@
+use crate::interface::{ConsoleInput, ConsoleLog, LogLevel};
+use crate::DataError;
+use serialport::SerialPort;
+use std::cell::RefCell;
+use std::io::{self, BufRead, BufReader, Write};
+use std::time::Duration;
+
+/// Serial port read timeout, the read is retried until a whole line arrives.
+const SERIAL_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Open the serial port of the path at the baud rate.
+fn port_open(path: &str, baud_rate: u32) -> Result<Box<dyn SerialPort>, DataError> {
+    serialport::new(path, baud_rate)
+        .timeout(SERIAL_TIMEOUT)
+        .open()
+        .map_err(|err_info| DataError::Other(err_info.into()))
+}
+
+/// Terminal commands read line by line from a serial port, e.g.
+/// `console.set_input(SerialSource::open("/dev/ttyUSB0", 115_200)?)`.
+pub struct SerialSource {
+    port: BufReader<Box<dyn SerialPort>>,
+}
+
+impl SerialSource {
+    pub fn open(path: &str, baud_rate: u32) -> Result<Self, DataError> {
+        Ok(SerialSource {
+            port: BufReader::new(port_open(path, baud_rate)?),
+        })
+    }
+
+    /// Another handle of the same port, e.g. for the `SerialLog` writing back to it.
+    pub fn try_clone_port(&self) -> Result<Box<dyn SerialPort>, DataError> {
+        self.port
+            .get_ref()
+            .try_clone()
+            .map_err(|err_info| DataError::Other(err_info.into()))
+    }
+}
+
+impl ConsoleInput for SerialSource {
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        let mut line = Vec::new();
+        loop {
+            match self.port.read_until(b'\n', &mut line) {
+                Ok(0) => break,
+                Ok(_) if line.ends_with(b"\n") => break,
+                Ok(_) => continue,
+                // wait for the rest of the line.
+                Err(err_info) if err_info.kind() == io::ErrorKind::TimedOut => continue,
+                Err(err_info) => return Err(err_info),
+            }
+        }
+        let line = String::from_utf8(line)
+            .map_err(|err_info| io::Error::new(io::ErrorKind::InvalidData, err_info))?;
+        buf.push_str(&line);
+        Ok(line.len())
+    }
+}
+
+/// Console log which also writes the file command execution output to a serial port.
+pub struct SerialLog {
+    port: RefCell<Box<dyn SerialPort>>,
+}
+
+impl SerialLog {
+    pub fn new(port: Box<dyn SerialPort>) -> Self {
+        SerialLog {
+            port: RefCell::new(port),
+        }
+    }
+}
+
+impl ConsoleLog for SerialLog {
+    fn file_exc_log(&self, log_info: &str) {
+        self.log(LogLevel::Info, log_info);
+        let mut port = self.port.borrow_mut();
+        if let Err(err_info) = writeln!(port, "{}", log_info) {
+            eprintln!("串口写入失败：{}", err_info);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::interface::{ConsoleInput, ConsoleLog, LogLevel};
+use crate::DataError;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+
+/// Connected client shared by the source and the log.
+type Client = Arc<Mutex<Option<TcpStream>>>;
+
+/// Terminal commands read line by line from a TCP client, one client at a time.
+/// When the client disconnects, the next client is waited for.
+///
+/// ```no_run
+/// use rfcaf::tcp::TcpSource;
+/// use std::sync::{Arc, Mutex};
+///
+/// let source = TcpSource::bind("127.0.0.1:7878").unwrap();
+/// let mut console = rfcaf::Console::new(Arc::new(Mutex::new(source.log())));
+/// console.set_input(source);
+/// console.setup();
+/// ```
+pub struct TcpSource {
+    listener: TcpListener,
+    reader: Option<BufReader<TcpStream>>, // <None while no client is connected> command reader.
+    client: Client,
+}
+
+impl TcpSource {
+    pub fn bind(addr: impl ToSocketAddrs) -> Result<Self, DataError> {
+        Ok(TcpSource {
+            listener: TcpListener::bind(addr)?,
+            reader: None,
+            client: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// Console log writing back to the connected client.
+    pub fn log(&self) -> TcpLog {
+        TcpLog {
+            client: self.client.clone(),
+        }
+    }
+
+    /// Wait for the next client.
+    fn accept(&mut self) -> io::Result<()> {
+        let (stream, _) = self.listener.accept()?;
+        *self.client.lock().unwrap_or_else(|e| e.into_inner()) = Some(stream.try_clone()?);
+        self.reader = Some(BufReader::new(stream));
+        Ok(())
+    }
+
+    fn disconnect(&mut self) {
+        self.reader = None;
+        *self.client.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+impl ConsoleInput for TcpSource {
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        loop {
+            let Some(reader) = self.reader.as_mut() else {
+                self.accept()?;
+                continue;
+            };
+            match BufRead::read_line(reader, buf) {
+                Ok(0) | Err(_) => self.disconnect(),
+                Ok(len) => return Ok(len),
+            }
+        }
+    }
+}
+
+/// Console log tee'd to stdout and the connected TCP client.
+#[derive(Debug)]
+pub struct TcpLog {
+    client: Client,
+}
+
+impl ConsoleLog for TcpLog {
+    fn log(&self, _level: LogLevel, log_info: &str) {
+        println!("{}", log_info);
+        let mut client = self.client.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(stream) = client.as_mut() {
+            // the disconnected client is dropped by the source on its next read.
+            if writeln!(stream, "{}", log_info).is_err() {
+                *client = None;
+            }
+        }
+    }
+}
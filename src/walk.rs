@@ -0,0 +1,167 @@
+use crate::interface::ConsoleLog;
+use crate::{Console, GenericCmd};
+
+/// Dry run of the file cursor from its current position: the commands `read` serves, by the
+/// same rules as the file cursor, without touching the console.
+///
+/// The later cycles of a shuffled file keep the current order. The instruction sets skipped
+/// after a failure and the lines of a streamed command list not read yet are not known.
+pub(crate) struct CursorWalk<'a, T: ConsoleLog> {
+    console: &'a Console<T>,
+    next_exc_ins: Option<usize>,
+    next_exc_cmd: Option<usize>,
+    ins_cycle_times: Option<usize>,
+    cycle_times: Option<usize>,
+    cycle_start: bool, // the cursor entered a new cycle from the start of the file.
+}
+
+impl<'a, T> CursorWalk<'a, T>
+where
+    T: ConsoleLog,
+{
+    pub(crate) fn new(console: &'a Console<T>) -> Self {
+        CursorWalk {
+            console,
+            next_exc_ins: console.auto_exc.next_exc_ins,
+            next_exc_cmd: console.auto_exc.next_exc_cmd,
+            ins_cycle_times: console.auto_exc.ins_cycle_times,
+            cycle_times: console.auto_exc.cycle_times,
+            cycle_start: false,
+        }
+    }
+
+    /// Count of the commands left. Without jumps, the whole cycles after the current one
+    /// are counted at once.
+    pub(crate) fn count_left(mut self) -> usize {
+        let exc_ins_assets = &self.console.auto_exc.exc_ins_assets;
+        let jumps = exc_ins_assets.iter().any(|exc_assets| {
+            let sub_cmds = exc_assets.sub_cmd_assets.iter().flatten();
+            std::iter::once(&exc_assets.exc_ins)
+                .chain(sub_cmds.map(|v| &v.sub_cmd))
+                .any(|cmd| cmd.goto_label().is_some())
+        });
+        let mut count = 0;
+        while self.next_exc_ins.is_some() {
+            if self.cycle_start && !jumps {
+                let cycle_times = self.cycle_times.unwrap_or(1);
+                return count + self.console.cycle_commands() * cycle_times;
+            }
+            self.advance();
+            count += 1;
+        }
+        count
+    }
+
+    /// The pending command, or the pending instruction if no command is pending.
+    fn pending(&self) -> Option<&'a GenericCmd> {
+        let auto_exc = &self.console.auto_exc;
+        let ins_index = self.next_exc_ins?;
+        match self.next_exc_cmd {
+            Some(cmd_index) => auto_exc.cmd_at(ins_index, cmd_index),
+            None => auto_exc.ins_at(ins_index),
+        }
+    }
+
+    /// Move to the next command, like `cursor_poll`.
+    fn advance(&mut self) {
+        let Some(ins_index) = self.next_exc_ins else {
+            return;
+        };
+        self.cycle_start = false;
+        let len = self.console.auto_exc.exc_ins_assets[ins_index]
+            .sub_cmd_assets
+            .as_ref()
+            .map_or(0, |v| v.len());
+        match self.next_exc_cmd {
+            None if len > 0 => self.next_exc_cmd = Some(0),
+            Some(cmd_index) if cmd_index + 1 < len => self.next_exc_cmd = Some(cmd_index + 1),
+            _ => self.ins_finish(ins_index, len),
+        }
+
+        // follow the jumps to the labeled instruction sets.
+        while let (Some(ins_index), Some(label)) = (
+            self.next_exc_ins,
+            self.pending().and_then(|v| v.goto_label()),
+        ) {
+            self.ins_goto(ins_index, label);
+        }
+    }
+
+    /// Repeat the instruction set of the index, or go to the next one, like `ins_finish`.
+    fn ins_finish(&mut self, ins_index: usize, len: usize) {
+        if let Some(cycle_times) = self.ins_cycle_times.filter(|v| *v > 1) {
+            self.ins_cycle_times = Some(cycle_times - 1);
+            self.next_exc_cmd = if len > 0 { Some(0) } else { None };
+            return;
+        }
+        self.ins_advance(self.console.ins_pos(ins_index) + 1);
+    }
+
+    /// Go to the first executable instruction set starting from the position, like
+    /// `ins_advance`.
+    fn ins_advance(&mut self, mut ins_pos: usize) {
+        let console = self.console;
+        let len = console.auto_exc.exc_ins_assets.len();
+        loop {
+            if ins_pos >= len {
+                self.next_exc_ins = None;
+                self.next_exc_cmd = None;
+                if len == 0 || console.auto_exc.stream.is_some() || !self.cycle_next() {
+                    return;
+                }
+                self.cycle_start = true;
+                ins_pos = 0;
+            }
+
+            let ins_index = console.ins_at(ins_pos);
+            if !console.ins_selected(ins_index) || console.ins_is_group(ins_index) {
+                ins_pos += 1;
+                continue;
+            }
+            self.next_exc_ins = Some(ins_index);
+            self.next_exc_cmd = None;
+            self.ins_cycle_times = console.auto_exc.exc_ins_assets[ins_index].cycle_times;
+            return;
+        }
+    }
+
+    /// Jump to the labeled instruction set, like `ins_goto`.
+    fn ins_goto(&mut self, ins_index: usize, label: &str) {
+        let console = self.console;
+        let target = console.auto_exc.labels.get(label).copied();
+        let jumped = target.is_some_and(|target| {
+            console.ins_pos(target) > console.ins_pos(ins_index) || self.cycle_next()
+        });
+        match target {
+            Some(target) if jumped => self.ins_advance(console.ins_pos(target)),
+            _ => {
+                self.next_exc_ins = None;
+                self.next_exc_cmd = None;
+            }
+        }
+    }
+
+    /// Consume one cycle, like `cycle_next`.
+    fn cycle_next(&mut self) -> bool {
+        match self.cycle_times {
+            Some(cycle_times) => {
+                self.cycle_times = Some(cycle_times.saturating_sub(1));
+                cycle_times.saturating_sub(1) != 0
+            }
+            None => false,
+        }
+    }
+}
+
+impl<'a, T> Iterator for CursorWalk<'a, T>
+where
+    T: ConsoleLog,
+{
+    type Item = &'a GenericCmd;
+
+    fn next(&mut self) -> Option<&'a GenericCmd> {
+        let cmd = self.pending()?;
+        self.advance();
+        Some(cmd)
+    }
+}
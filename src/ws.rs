@@ -0,0 +1,149 @@
+use crate::color;
+use crate::interface::{state_banner, ConsoleInput, ConsoleLog, LogLevel};
+use crate::DataError;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::json;
+use std::io;
+use std::net::{TcpListener, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Terminal commands received from a WebSocket client, each text frame is one command line
+/// and goes through the same validation as the terminal input. One client is served at a time.
+///
+/// The console has no async read path, so the connection is served by a tokio runtime on a
+/// background thread and the received commands reach the blocking `read` through a channel.
+/// The log output of `WsLog` is pushed back to the client as JSON text frames, without the
+/// ANSI color codes. At most 1024 frames are queued, the oldest ones are dropped when
+/// the client falls behind, and the frames logged while no client is attached are not kept:
+///
+/// - `{"kind":"log","level":"info","msg":"..."}` for every log information,
+///   the level is one of `trace`, `debug`, `info`, `warn` and `error`.
+/// - `{"kind":"state","prev":"InsExecFromTerminal","next":"InsAcqFromFile"}` for every
+///   console state transition, the frontend can render `next` as the current state:
+///   `InsAcqFromTerminal` waits for the terminal, `InsExecFromTerminal` executes it,
+///   `InsAcqFromFile` and `InsExecFromFile` run the imported file.
+///
+/// ```no_run
+/// use rfcaf::ws::WebSocketSource;
+/// use std::sync::{Arc, Mutex};
+///
+/// let source = WebSocketSource::bind("127.0.0.1:9001").unwrap();
+/// let mut console = rfcaf::Console::new(Arc::new(Mutex::new(source.log())));
+/// console.set_input(source);
+/// console.setup();
+/// ```
+pub struct WebSocketSource {
+    commands: Receiver<String>,
+    frames: broadcast::Sender<String>,
+}
+
+/// Capacity of the queue of the frames pushed to the client.
+const FRAMES_MAX: usize = 1024;
+
+impl WebSocketSource {
+    pub fn bind(addr: impl ToSocketAddrs) -> Result<Self, DataError> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let (command_sender, commands) = mpsc::channel();
+        let (frames, frame_receiver) = broadcast::channel(FRAMES_MAX);
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_io()
+            .build()?;
+        thread::spawn(move || {
+            runtime.block_on(async move {
+                if let Err(err_info) = serve(listener, command_sender, frame_receiver).await {
+                    eprintln!("WebSocket 服务退出：{}", err_info);
+                }
+            })
+        });
+
+        Ok(WebSocketSource { commands, frames })
+    }
+
+    /// Console log pushing the output back to the connected client.
+    pub fn log(&self) -> WsLog {
+        WsLog {
+            frames: self.frames.clone(),
+        }
+    }
+}
+
+/// Serve the clients one after another until the console is dropped.
+async fn serve(
+    listener: TcpListener,
+    commands: Sender<String>,
+    mut frames: broadcast::Receiver<String>,
+) -> io::Result<()> {
+    let listener = tokio::net::TcpListener::from_std(listener)?;
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let Ok(socket) = tokio_tungstenite::accept_async(stream).await else {
+            continue;
+        };
+        let (mut sink, mut stream) = socket.split();
+        // skip the frames queued before the client attached.
+        frames = frames.resubscribe();
+        loop {
+            tokio::select! {
+                message = stream.next() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        if commands.send(text.to_string()).is_err() {
+                            return Ok(());
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                },
+                frame = frames.recv() => match frame {
+                    Ok(frame) => {
+                        if sink.send(Message::Text(frame.into())).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => return Ok(()),
+                },
+            }
+        }
+    }
+}
+
+impl ConsoleInput for WebSocketSource {
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        match self.commands.recv() {
+            Ok(line) => {
+                buf.push_str(&line);
+                buf.push('\n');
+                Ok(line.len() + 1)
+            }
+            // the server thread exited.
+            Err(_) => Ok(0),
+        }
+    }
+}
+
+/// Console log tee'd to stdout and the WebSocket client as JSON text frames.
+#[derive(Debug)]
+pub struct WsLog {
+    frames: broadcast::Sender<String>,
+}
+
+impl ConsoleLog for WsLog {
+    fn log(&self, level: LogLevel, log_info: &str) {
+        println!("{}", log_info);
+        let level = format!("{:?}", level).to_lowercase();
+        let frame = json!({ "kind": "log", "level": level, "msg": color::strip(log_info) });
+        let _ = self.frames.send(frame.to_string());
+    }
+
+    fn state_log(&self, prev: &str, next: &str) {
+        println!("{}", state_banner(prev, next));
+        let (prev, next) = (color::strip(prev), color::strip(next));
+        let frame = json!({ "kind": "state", "prev": prev, "next": next });
+        let _ = self.frames.send(frame.to_string());
+    }
+}
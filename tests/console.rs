@@ -0,0 +1,634 @@
+use rfcaf::capture_log::CaptureLog;
+use rfcaf::interface::ConsoleInput;
+use rfcaf::{
+    Console, ConsoleStatus, DataError, ErrorKind, ExecuteFile, FileFormat, InputPolicy,
+    InstructionSelector,
+};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::io::{self, BufRead, Cursor};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+/// Console reading the terminal lines of the input, logging to the returned capture.
+fn console(input: &str) -> (Arc<Mutex<CaptureLog>>, Console<CaptureLog>) {
+    let log = Arc::new(Mutex::new(CaptureLog::new()));
+    let mut console = Console::new(log.clone());
+    #[cfg(feature = "colored")]
+    console.set_color(false);
+    console.set_log_timestamps(false);
+    console.set_input(Cursor::new(input.as_bytes().to_vec()));
+    console.setup();
+    (log, console)
+}
+
+/// The logged prompts, in order.
+fn prompts(log: &Mutex<CaptureLog>) -> Vec<String> {
+    let lines = log.lock().unwrap().lines();
+    lines.into_iter().filter(|v| v.starts_with("> ")).collect()
+}
+
+/// Read the commands until the input is exhausted.
+fn drain(console: &mut Console<CaptureLog>) -> Vec<String> {
+    let mut cmds = Vec::new();
+    loop {
+        match console.read("") {
+            Ok(cmd) => cmds.push(cmd),
+            Err(DataError::Eof) => return cmds,
+            Err(err_info) => panic!("{:?}", err_info),
+        }
+        console.taildowm();
+    }
+}
+
+#[test]
+fn echo_depth_keeps_recent_segments() {
+    let (log, mut console) = console("a\nb\nc\nd\ne\n");
+    console.set_echo_depth(2);
+    for cmd in ["a", "b", "c", "d", "e"] {
+        assert_eq!(console.read("").unwrap(), cmd);
+    }
+    assert_eq!(
+        prompts(&log),
+        [
+            "> ",
+            "> a > ",
+            "> a > b > ",
+            "> ... > b > c > ",
+            "> ... > c > d > ",
+        ]
+    );
+}
+
+#[test]
+fn validate_collecting_reports_every_problem() {
+    let content = r#"
+cycle_times = 0
+
+[[exc_ins_assets]]
+exc_ins = "a"
+sub_cmd_assets = []
+
+[[exc_ins_assets]]
+exc_ins = "b"
+cycle_times = 0
+"#;
+    let errors: Vec<String> = rfcaf::validate_collecting(content)
+        .iter()
+        .map(|v| v.to_string())
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            "执行次数不能为 0。",
+            "第 1 条主指令集的子命令集为空。",
+            "第 2 条主指令集的执行次数不能为 0。",
+        ]
+    );
+}
+
+#[test]
+fn instruction_cycle_times_repeats_its_commands() {
+    let content = r#"
+[[exc_ins_assets]]
+exc_ins = "a"
+[[exc_ins_assets.sub_cmd_assets]]
+sub_cmd = "a1"
+
+[[exc_ins_assets]]
+exc_ins = "b"
+cycle_times = 2
+[[exc_ins_assets.sub_cmd_assets]]
+sub_cmd = "b1"
+[[exc_ins_assets.sub_cmd_assets]]
+sub_cmd = "b2"
+
+[[exc_ins_assets]]
+exc_ins = "c"
+[[exc_ins_assets.sub_cmd_assets]]
+sub_cmd = "c1"
+"#;
+    let (_log, mut console) = console("");
+    console.import_from_str(content, FileFormat::Toml).unwrap();
+    assert_eq!(
+        drain(&mut console),
+        ["a", "a1", "b", "b1", "b2", "b1", "b2", "c", "c1"]
+    );
+}
+
+#[test]
+fn group_only_instruction_is_not_read() {
+    let content = r#"
+[[exc_ins_assets]]
+exc_ins = "group"
+
+[[exc_ins_assets]]
+exc_ins = "a"
+[[exc_ins_assets.sub_cmd_assets]]
+sub_cmd = "a1"
+"#;
+    let (_log, mut console) = console("");
+    console.set_group_only_instructions(true);
+    console.import_from_str(content, FileFormat::Toml).unwrap();
+    assert_eq!(drain(&mut console), ["a", "a1"]);
+}
+
+#[test]
+fn read_batch_stops_at_the_end_of_the_file() {
+    let (_log, mut console) = console("");
+    console
+        .import_from_str("a\nb\nc\nd\ne\n", FileFormat::List)
+        .unwrap();
+    assert_eq!(console.read_batch(3).unwrap(), ["a", "b", "c"]);
+    assert_eq!(console.read_batch(3).unwrap(), ["d", "e"]);
+}
+
+#[test]
+fn run_summary_is_logged_once() {
+    let content = r#"
+[[exc_ins_assets]]
+exc_ins = "a"
+[[exc_ins_assets.sub_cmd_assets]]
+sub_cmd = "a1"
+
+[[exc_ins_assets]]
+exc_ins = "b"
+"#;
+    let (log, mut console) = console("");
+    console.import_from_str(content, FileFormat::Toml).unwrap();
+    assert_eq!(drain(&mut console).len(), 3);
+
+    let summary = console.run_summary();
+    assert_eq!((summary.commands, summary.failures), (3, 0));
+    let lines = log.lock().unwrap().lines();
+    let summaries: Vec<_> = lines
+        .into_iter()
+        .filter(|v| v.starts_with(" * 执行命令"))
+        .collect();
+    assert_eq!(summaries.len(), 1);
+    assert!(summaries[0].starts_with(" * 执行命令 3 条，失败 0 条，"));
+}
+
+#[test]
+fn abort_falls_back_to_the_terminal() {
+    let (_log, mut console) = console("typed\n");
+    console
+        .import_from_str("a\nb\nc\n", FileFormat::List)
+        .unwrap();
+    assert_eq!(console.read("").unwrap(), "a");
+    console.abort().unwrap();
+    assert_eq!(console.read("").unwrap(), "typed");
+}
+
+#[test]
+fn exhausted_input_is_eof() {
+    let (_log, mut console) = console("");
+    assert!(matches!(console.read(""), Err(DataError::Eof)));
+}
+
+/// Terminal input recording the lines read as commands, like the history of the line editor.
+struct HistoryInput {
+    lines: Cursor<Vec<u8>>,
+    history: Rc<RefCell<Vec<String>>>,
+}
+
+impl ConsoleInput for HistoryInput {
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        let len = BufRead::read_line(&mut self.lines, buf)?;
+        self.history.borrow_mut().push(buf.trim_end().to_string());
+        Ok(len)
+    }
+
+    fn read_answer_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        BufRead::read_line(&mut self.lines, buf)
+    }
+}
+
+#[test]
+fn prompt_answers_are_not_recorded() {
+    let (_log, mut console) = console("");
+    let history = Rc::new(RefCell::new(Vec::new()));
+    console.set_input(HistoryInput {
+        lines: Cursor::new(b"maybe\ny\n2\ncmd\n".to_vec()),
+        history: history.clone(),
+    });
+    assert!(console.confirm("continue?").unwrap());
+    assert_eq!(console.select("pick", &["a", "b"]).unwrap(), 1);
+    assert_eq!(console.read("").unwrap(), "cmd");
+    assert_eq!(*history.borrow(), ["cmd"]);
+}
+
+#[test]
+fn default_policy_accepts_unicode_letters() {
+    let (_log, mut console) = console("设备 三号\ncafé naïve\n");
+    assert_eq!(console.read("").unwrap(), "设备 三号");
+    assert_eq!(console.read("").unwrap(), "café naïve");
+}
+
+#[test]
+fn default_policy_rejects_emoji() {
+    let (_log, mut console) = console("run 🚀\n");
+    assert!(matches!(
+        console.read(""),
+        Err(DataError::InvalidHeader { .. })
+    ));
+}
+
+#[test]
+fn policy_accepts_extra_characters() {
+    let (_log, mut console) = console("run 🚀\n");
+    console.set_input_policy(InputPolicy {
+        extra: vec!['🚀'],
+        ..InputPolicy::default()
+    });
+    assert_eq!(console.read("").unwrap(), "run 🚀");
+}
+
+#[test]
+fn malformed_toml_str_is_redaction() {
+    assert!(matches!(
+        ExecuteFile::from_toml_str("cycle_times = \"twice\""),
+        Err(DataError::Redaction(_))
+    ));
+}
+
+#[test]
+fn quiet_keeps_help_and_select_options() {
+    let (log, mut console) = console("help\nrun\n2\n");
+    console.set_quiet(true);
+    console.register_command("run", Some("执行"));
+    log.lock().unwrap().clear();
+    assert_eq!(console.read("").unwrap(), "run");
+    console.taildowm();
+    assert_eq!(console.select("选择", &["甲", "乙"]).unwrap(), 1);
+    assert_eq!(
+        log.lock().unwrap().lines(),
+        ["可用命令：", "  run  执行", "run", "  1. 甲", "  2. 乙"]
+    );
+}
+
+#[test]
+fn instruction_numbers_count_from_one() {
+    let (_log, mut console) = console("");
+    let content = "[[exc_ins_assets]]\nexc_ins = \"a\"\n\n[[exc_ins_assets]]\nexc_ins = \"b\"\n";
+    console.import_from_str(content, FileFormat::Toml).unwrap();
+    match console.run_only(InstructionSelector::Index(2)) {
+        Err(DataError::Redaction(err_info)) => assert_eq!(err_info, "第 3 条主指令集不存在。"),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn denied_command_is_not_executed() {
+    let (_log, mut console) = console("rm\n");
+    console.deny_commands(HashSet::from(["rm".to_string()]));
+    assert!(matches!(console.read(""), Err(DataError::Redaction(_))));
+    assert_eq!(console.current_status(), ConsoleStatus::InsAcqFromTerminal);
+}
+
+#[test]
+fn skipping_the_last_instruction_ends_the_run() {
+    let content = r#"
+[[exc_ins_assets]]
+exc_ins = "a"
+
+[[exc_ins_assets]]
+exc_ins = "b"
+skip_if_prev_failed = true
+"#;
+    let (_log, mut console) = console("typed\n");
+    console.import_from_str(content, FileFormat::Toml).unwrap();
+    assert_eq!(console.read("").unwrap(), "a");
+    console.set_last_result(false);
+    console.taildowm();
+    assert_eq!(console.read("").unwrap(), "typed");
+
+    let summary = console.run_summary();
+    assert_eq!((summary.commands, summary.failures), (1, 1));
+}
+
+#[test]
+fn read_batch_keeps_the_commands_before_an_error() {
+    let (_log, mut console) = console("");
+    console
+        .import_from_str("a\nb\nc?\nd\ne\n", FileFormat::List)
+        .unwrap();
+    assert_eq!(console.read_batch(4).unwrap(), ["a", "b"]);
+    assert!(matches!(
+        console.read_batch(4),
+        Err(DataError::InvalidHeader { .. })
+    ));
+    assert_eq!(console.read_batch(4).unwrap(), ["d", "e"]);
+}
+
+#[test]
+fn breadcrumb_numbers_count_from_one() {
+    let content = r#"
+[[exc_ins_assets]]
+exc_ins = "init"
+
+[[exc_ins_assets]]
+exc_ins = "run"
+label = "main"
+[[exc_ins_assets.sub_cmd_assets]]
+sub_cmd = "cal"
+[[exc_ins_assets.sub_cmd_assets]]
+sub_cmd = "check"
+"#;
+    let (log, mut console) = console("");
+    console.set_breadcrumb(true);
+    console.import_from_str(content, FileFormat::Toml).unwrap();
+    drain(&mut console);
+    assert_eq!(
+        prompts(&log)[..4],
+        [
+            "> init[1] > ",
+            "> main[2] > ",
+            "> main[2] > cal[1] > ",
+            "> main[2] > check[2] > ",
+        ]
+    );
+}
+
+/// File of a grouping instruction set, a jump over an instruction set and a backward jump
+/// starting the second cycle.
+const JUMPS: &str = r#"
+cycle_times = 2
+
+[[exc_ins_assets]]
+exc_ins = "group"
+
+[[exc_ins_assets]]
+exc_ins = "a"
+label = "start"
+[[exc_ins_assets.sub_cmd_assets]]
+sub_cmd = "a1"
+[[exc_ins_assets.sub_cmd_assets]]
+sub_cmd = "goto end"
+
+[[exc_ins_assets]]
+exc_ins = "skipped"
+[[exc_ins_assets.sub_cmd_assets]]
+sub_cmd = "skipped1"
+
+[[exc_ins_assets]]
+exc_ins = "b"
+label = "end"
+cycle_times = 2
+[[exc_ins_assets.sub_cmd_assets]]
+sub_cmd = "b1"
+
+[[exc_ins_assets]]
+exc_ins = "goto start"
+"#;
+
+/// (total at the import, progress at the end) of the file run, group-only.
+fn run_progress(content: &str) -> (usize, (usize, usize)) {
+    let (_log, mut console) = console("");
+    console.set_group_only_instructions(true);
+    console.import_from_str(content, FileFormat::Toml).unwrap();
+    let total = console.progress().1;
+    drain(&mut console);
+    (total, console.progress())
+}
+
+#[test]
+fn progress_passes_the_grouping_instruction_sets() {
+    let content = JUMPS.replace("goto end", "a2").replace("goto start", "c");
+    assert_eq!(run_progress(&content), (16, (16, 16)));
+}
+
+#[test]
+fn progress_follows_the_jumps() {
+    assert_eq!(run_progress(JUMPS), (10, (10, 10)));
+}
+
+#[test]
+fn progress_drops_the_skipped_instruction_sets() {
+    let content = r#"
+[[exc_ins_assets]]
+exc_ins = "a"
+
+[[exc_ins_assets]]
+exc_ins = "b"
+skip_if_prev_failed = true
+[[exc_ins_assets.sub_cmd_assets]]
+sub_cmd = "b1"
+
+[[exc_ins_assets]]
+exc_ins = "c"
+"#;
+    let (_log, mut console) = console("");
+    console.import_from_str(content, FileFormat::Toml).unwrap();
+    assert_eq!(console.progress(), (0, 4));
+    assert_eq!(console.read("").unwrap(), "a");
+    console.set_last_result(false);
+    assert_eq!(console.read("").unwrap(), "c");
+    assert_eq!(console.progress(), (2, 2));
+}
+
+#[test]
+fn commands_walks_like_read() {
+    let (_log, mut console) = console("");
+    console.set_group_only_instructions(true);
+    console.import_from_str(JUMPS, FileFormat::Toml).unwrap();
+    let commands: Vec<String> = console.commands().collect();
+    assert_eq!(
+        commands,
+        ["a", "a1", "b", "b1", "b1", "a", "a1", "b", "b1", "b1"]
+    );
+    assert_eq!(commands, drain(&mut console));
+}
+
+#[test]
+fn pause_keeps_reading_the_terminal() {
+    let (_log, mut console) = console("resume\n");
+    console.pause();
+    assert_eq!(console.read("").unwrap(), "resume");
+
+    console.import_from_str("a\n", FileFormat::List).unwrap();
+    assert!(matches!(console.read(""), Err(DataError::Paused)));
+}
+
+#[test]
+fn modified_file_restarts_the_run() {
+    let path = std::env::temp_dir().join(format!("rfcaf-watch-{}.toml", std::process::id()));
+    let content = |ins: &str| {
+        format!(
+            "[[exc_ins_assets]]\nexc_ins = \"{}\"\n\n[[exc_ins_assets]]\nexc_ins = \"end\"\n",
+            ins
+        )
+    };
+    std::fs::write(&path, content("a")).unwrap();
+    let (_log, mut console) = console(&format!("{}\n", path.display()));
+    console.set_input_policy(InputPolicy {
+        extra: vec!['/', '_'],
+        ..InputPolicy::default()
+    });
+    console.file_import().unwrap();
+    console.enable_file_watch().unwrap();
+    assert_eq!(console.read("").unwrap(), "a");
+
+    std::fs::write(&path, content("b")).unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    assert_eq!(console.read("").unwrap(), "b");
+    assert_eq!(console.progress(), (1, 2));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "colored")]
+#[test]
+fn capture_log_keeps_no_colors() {
+    let (log, mut console) = console("bad?\n");
+    console.set_color(true);
+    assert!(console.read("").is_err());
+    let lines = log.lock().unwrap().lines();
+    assert!(!lines.is_empty());
+    assert!(lines.iter().all(|v| !v.contains('\x1b')));
+}
+
+#[test]
+fn import_dir_keeps_the_error_kind() {
+    let dir = std::env::temp_dir().join(format!("rfcaf-dir-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.toml"), "[[exc_ins_assets]]\nexc_ins = \"a\"\n").unwrap();
+    std::fs::write(dir.join("b.toml"), "cycle_times = \"twice\"\n").unwrap();
+    let (_log, mut console) = console("");
+    let err_info = console.import_dir(&dir).unwrap_err();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(err_info.kind(), ErrorKind::Parse);
+    match err_info {
+        DataError::File { path, .. } => assert_eq!(path, dir.join("b.toml")),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn run_log_is_opened_by_each_run() {
+    let dir = std::env::temp_dir().join(format!("rfcaf-runs-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (_log, mut console) = console("");
+    console.set_run_log_dir(Some(dir.clone()));
+    let run_logs = || std::fs::read_dir(&dir).unwrap().count();
+
+    console.import_from_str("a\n", FileFormat::List).unwrap();
+    assert_eq!(run_logs(), 0);
+    for runs in 1..=2 {
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        console.import_from_str("a\nb\n", FileFormat::List).unwrap();
+        console.run_to_completion().unwrap();
+        assert_eq!(run_logs(), runs);
+    }
+    for entry in std::fs::read_dir(&dir).unwrap() {
+        let content = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+        assert!(content.contains(" * 执行命令 2 条，失败 0 条，"));
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Commands read after the edit of the file run paused before `c`, and whether the progress
+/// counted them all.
+fn edited_run(edit: impl FnOnce(&mut Console<CaptureLog>)) -> (Vec<String>, bool) {
+    let (_log, mut console) = console("");
+    console
+        .import_from_str("a\nb\nc\nd\n", FileFormat::List)
+        .unwrap();
+    assert_eq!(drain_n(&mut console, 2), ["a", "b"]);
+    console.pause();
+    edit(&mut console);
+    console.resume();
+    let cmds = drain(&mut console);
+    let (read, total) = console.progress();
+    (cmds, read == total)
+}
+
+/// Read the next commands.
+fn drain_n(console: &mut Console<CaptureLog>, n: usize) -> Vec<String> {
+    (0..n)
+        .map(|_| {
+            let cmd = console.read("").unwrap();
+            console.taildowm();
+            cmd
+        })
+        .collect()
+}
+
+#[test]
+fn remove_instruction_around_the_paused_cursor() {
+    let remove = |index| {
+        edited_run(move |console| {
+            console.remove_instruction(index).unwrap();
+        })
+    };
+    assert_eq!(remove(0), (vec!["c".to_string(), "d".to_string()], true));
+    assert_eq!(remove(2), (vec!["d".to_string()], true));
+    assert_eq!(remove(3), (vec!["c".to_string()], true));
+}
+
+#[test]
+fn move_instruction_around_the_paused_cursor() {
+    let move_to = |from, to| edited_run(move |console| console.move_instruction(from, to).unwrap());
+    assert_eq!(
+        move_to(0, 3),
+        (
+            vec!["c".to_string(), "d".to_string(), "a".to_string()],
+            true
+        )
+    );
+    assert_eq!(
+        move_to(2, 0),
+        (
+            vec![
+                "c".to_string(),
+                "a".to_string(),
+                "b".to_string(),
+                "d".to_string()
+            ],
+            true
+        )
+    );
+    assert_eq!(move_to(3, 0), (vec!["c".to_string()], true));
+}
+
+#[test]
+fn read_block_holds_at_most_the_length_limit() {
+    const LIMIT: usize = 64 * 1024;
+    let read_block = |lines: &[String]| {
+        let (_, mut console) = console(&format!("{}\nEOF\n", lines.join("\n")));
+        console.read_block("", "EOF").map(|v| v.len())
+    };
+    let line = |len| "x".repeat(len);
+    assert_eq!(read_block(&[line(LIMIT)]).unwrap(), LIMIT);
+    assert!(read_block(&[line(LIMIT + 1)]).is_err());
+    assert_eq!(
+        read_block(&[line(LIMIT / 2), line(LIMIT / 2 - 1)]).unwrap(),
+        LIMIT
+    );
+    assert!(read_block(&[line(LIMIT / 2), line(LIMIT / 2)]).is_err());
+}
+
+#[test]
+fn execute_file_round_trips_without_the_cursor() {
+    let content = "cycle_times = 2\nnext_exc_ins = 1\nnext_exc_cmd = 0\nins_cycle_times = 3\n\n\
+                   [[exc_ins_assets]]\nexc_ins = \"a\"\n\n[[exc_ins_assets]]\nexc_ins = \"b\"\n";
+    let toml = ExecuteFile::from_toml_str(content)
+        .unwrap()
+        .to_toml()
+        .unwrap();
+    for key in [
+        "next_exc_ins",
+        "next_exc_cmd",
+        "ins_cycle_times",
+        "file_address",
+    ] {
+        assert!(!toml.contains(key), "{}", toml);
+    }
+    let exc_file = ExecuteFile::from_toml_str(&toml).unwrap();
+    assert_eq!(exc_file.to_toml().unwrap(), toml);
+
+    // the cursor keys of the content are ignored, the run starts from the first instruction.
+    let (_, mut console) = console("");
+    console.load(exc_file).unwrap();
+    assert_eq!(drain(&mut console), ["a", "b", "a", "b"]);
+}